@@ -0,0 +1,28 @@
+// assets.rs
+
+use image::ImageResult;
+use std::collections::HashMap;
+use std::rc::Rc;
+use crate::texture::Texture;
+
+// Cache de imágenes: cada archivo se decodifica una sola vez y se comparte
+#[derive(Default)]
+pub struct Assets {
+    images: HashMap<String, Rc<Texture>>,
+}
+
+impl Assets {
+    pub fn new() -> Self {
+        Assets::default()
+    }
+
+    pub fn image(&mut self, path: &str) -> ImageResult<Rc<Texture>> {
+        if let Some(texture) = self.images.get(path) {
+            return Ok(texture.clone());
+        }
+
+        let texture = Rc::new(Texture::load(path)?);
+        self.images.insert(path.to_string(), texture.clone());
+        Ok(texture)
+    }
+}
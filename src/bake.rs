@@ -0,0 +1,205 @@
+// bake.rs
+
+use nalgebra_glm::Vec3;
+use fastnoise_lite::FastNoiseLite;
+use image::{ImageBuffer, Luma};
+use std::fs;
+use std::path::Path;
+use crate::texture::{Texture, direction_to_equirect, equirect_to_direction};
+use crate::shader::surface_color;
+use crate::color::Color;
+
+// Cambiar si se modifican las capas de `shader::surface_color`, para invalidar la caché
+const BAKE_VERSION: u32 = 4;
+
+pub struct BakedSurface {
+    pub color: Texture,
+    pub height: Vec<f32>, // Altura normalizada [0, 1], mismo tamaño que `color`
+}
+
+impl BakedSurface {
+    pub fn sample(&self, direction: &Vec3) -> Color {
+        self.color.sample_direction(direction)
+    }
+
+    pub fn height_at(&self, direction: &Vec3) -> f32 {
+        let (u, v) = direction_to_equirect(direction);
+        let x = ((u * self.color.width as f32) as usize).min(self.color.width - 1);
+        let y = ((v * self.color.height as f32) as usize).min(self.color.height - 1);
+        self.height[y * self.color.width + x]
+    }
+}
+
+// Hornea las capas estáticas de un cuerpo en una textura equirectangular.
+// Devuelve `None` si el cuerpo no tiene capas estáticas.
+pub fn bake_surface(number: u8, noise: &FastNoiseLite, width: usize, height: usize) -> Option<BakedSurface> {
+    let mut color = Texture::new(width, height);
+    let mut heights = vec![0.0; width * height];
+
+    for y in 0..height {
+        for x in 0..width {
+            let u = (x as f32 + 0.5) / width as f32;
+            let v = (y as f32 + 0.5) / height as f32;
+            let direction = equirect_to_direction(u, v);
+
+            let (texel, texel_height) = surface_color(number, noise, &direction)?;
+            color.set(x, y, texel);
+            heights[y * width + x] = texel_height;
+        }
+    }
+
+    Some(BakedSurface { color, height: heights })
+}
+
+// Carga la superficie horneada desde `cache_dir` o la hornea y la guarda.
+// Los archivos se nombran con un hash de los parámetros del ruido, por lo que
+// cambiar cualquier ajuste genera una nueva entrada.
+pub fn load_or_bake(
+    cache_dir: &str,
+    number: u8,
+    noise: &FastNoiseLite,
+    width: usize,
+    height: usize,
+) -> Option<BakedSurface> {
+    let key = cache_key(number, noise, width, height);
+    let color_path = format!("{}/body{}_{:016x}.png", cache_dir, number, key);
+    let height_path = format!("{}/body{}_{:016x}_height.png", cache_dir, number, key);
+
+    if Path::new(&color_path).exists() && Path::new(&height_path).exists() {
+        match load_cached(&color_path, &height_path, width, height) {
+            Ok(surface) => return Some(surface),
+            Err(err) => eprintln!("Ignoring baked surface cache {}: {}", color_path, err),
+        }
+    }
+
+    let surface = bake_surface(number, noise, width, height)?;
+
+    if let Err(err) = save_cached(&surface, cache_dir, &color_path, &height_path) {
+        eprintln!("Could not write baked surface cache {}: {}", color_path, err);
+    }
+
+    Some(surface)
+}
+
+fn load_cached(color_path: &str, height_path: &str, width: usize, height: usize) -> Result<BakedSurface, String> {
+    let color = Texture::load(color_path).map_err(|e| e.to_string())?;
+    let heights = image::open(height_path).map_err(|e| e.to_string())?.to_luma16();
+
+    if color.width != width || color.height != height || heights.dimensions() != (width as u32, height as u32) {
+        return Err("cached texture has the wrong size".to_string());
+    }
+
+    let heights = heights.pixels().map(|p| p.0[0] as f32 / u16::MAX as f32).collect();
+
+    Ok(BakedSurface { color, height: heights })
+}
+
+fn save_cached(surface: &BakedSurface, cache_dir: &str, color_path: &str, height_path: &str) -> Result<(), String> {
+    fs::create_dir_all(cache_dir).map_err(|e| e.to_string())?;
+    surface.color.save(color_path).map_err(|e| e.to_string())?;
+
+    let width = surface.color.width as u32;
+    let height = surface.color.height as u32;
+    let heights: ImageBuffer<Luma<u16>, Vec<u16>> = ImageBuffer::from_fn(width, height, |x, y| {
+        let value = surface.height[(y * width + x) as usize].clamp(0.0, 1.0);
+        Luma([(value * u16::MAX as f32) as u16])
+    });
+    heights.save(height_path).map_err(|e| e.to_string())
+}
+
+// FNV-1a sobre los parámetros públicos del ruido; estable entre ejecuciones
+fn cache_key(number: u8, noise: &FastNoiseLite, width: usize, height: usize) -> u64 {
+    let settings = format!(
+        "{}|{}|{}x{}|{}|{}|{:?}|{:?}|{:?}|{}|{}|{}|{}|{}|{:?}|{:?}|{}|{:?}|{}",
+        BAKE_VERSION,
+        number,
+        width,
+        height,
+        noise.seed,
+        noise.frequency,
+        noise.noise_type,
+        noise.rotation_type_3d,
+        noise.fractal_type,
+        noise.octaves,
+        noise.lacunarity,
+        noise.gain,
+        noise.weighted_strength,
+        noise.ping_pong_strength,
+        noise.cellular_distance_function,
+        noise.cellular_return_type,
+        noise.cellular_jitter_modifier,
+        noise.domain_warp_type,
+        noise.domain_warp_amp,
+    );
+
+    settings.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fastnoise_lite::{CellularDistanceFunction, CellularReturnType, DomainWarpType, FractalType, NoiseType, RotationType3D};
+
+    #[test]
+    fn cache_key_changes_with_every_parameter() {
+        let base = cache_key(1, &FastNoiseLite::default(), 64, 32);
+        assert_eq!(base, cache_key(1, &FastNoiseLite::default(), 64, 32));
+
+        assert_ne!(base, cache_key(3, &FastNoiseLite::default(), 64, 32));
+        assert_ne!(base, cache_key(1, &FastNoiseLite::default(), 128, 32));
+        assert_ne!(base, cache_key(1, &FastNoiseLite::default(), 64, 64));
+
+        let changes: [fn(&mut FastNoiseLite); 15] = [
+            |noise| noise.seed += 1,
+            |noise| noise.frequency *= 2.0,
+            |noise| noise.noise_type = NoiseType::Perlin,
+            |noise| noise.rotation_type_3d = RotationType3D::ImproveXZPlanes,
+            |noise| noise.fractal_type = FractalType::Ridged,
+            |noise| noise.octaves += 1,
+            |noise| noise.lacunarity += 0.5,
+            |noise| noise.gain += 0.1,
+            |noise| noise.weighted_strength += 0.1,
+            |noise| noise.ping_pong_strength += 0.1,
+            |noise| noise.cellular_distance_function = CellularDistanceFunction::Manhattan,
+            |noise| noise.cellular_return_type = CellularReturnType::Distance2Add,
+            |noise| noise.cellular_jitter_modifier += 0.1,
+            |noise| noise.domain_warp_type = DomainWarpType::BasicGrid,
+            |noise| noise.domain_warp_amp += 1.0,
+        ];
+        for (i, change) in changes.iter().enumerate() {
+            let mut noise = FastNoiseLite::default();
+            change(&mut noise);
+            assert_ne!(base, cache_key(1, &noise, 64, 32), "change {} kept the same key", i);
+        }
+    }
+
+    #[test]
+    fn second_load_reads_the_cached_png() {
+        let dir = std::env::temp_dir().join(format!("bake-test-{}", std::process::id()));
+        let dir = dir.to_string_lossy().into_owned();
+        let noise = FastNoiseLite::default();
+
+        let baked = load_or_bake(&dir, 3, &noise, 16, 8).unwrap();
+        let color_path = format!("{}/body3_{:016x}.png", dir, cache_key(3, &noise, 16, 8));
+        assert!(Path::new(&color_path).exists());
+
+        // Se reemplaza la textura guardada: si la segunda carga horneara de nuevo no la vería
+        Texture::from_pixels(16, 8, vec![0xFFFF_00FF; 16 * 8]).save(&color_path).unwrap();
+
+        let cached = load_or_bake(&dir, 3, &noise, 16, 8).unwrap();
+        assert_eq!(cached.color.get(5, 5).to_hex(), 0xFF00FF);
+        assert_eq!(cached.height.len(), 16 * 8);
+        // La altura se guarda en 16 bits
+        for (cached, baked) in cached.height.iter().zip(&baked.height) {
+            assert!((cached - baked).abs() < 1e-4);
+        }
+
+        // Otro tamaño es otra entrada y se hornea aparte
+        let other = load_or_bake(&dir, 3, &noise, 8, 4).unwrap();
+        assert_ne!(other.color.get(1, 1).to_hex(), 0xFF00FF);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
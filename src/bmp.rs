@@ -1,34 +1,174 @@
 // bmp.rs
 
 use std::fs::File;
-use std::io::{Write, BufWriter, Result};
+use std::io::{BufReader, BufWriter, Error, ErrorKind, Read, Result, Write};
 
-const BMP_HEADER_SIZE: usize = 54;
-const BMP_PIXEL_OFFSET: usize = 54;
-const BMP_BITS_PER_PIXEL: usize = 32;
+const FILE_HEADER_SIZE: usize = 14;
+const INFO_HEADER_SIZE: usize = 40;
+const BMP_PIXEL_OFFSET: usize = FILE_HEADER_SIZE + INFO_HEADER_SIZE;
+
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+// Bits por píxel al escribir
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BmpDepth {
+    Rgb24,  // Sin alfa, filas rellenadas a múltiplos de 4 bytes
+    Argb32, // Guarda el byte alto del píxel como alfa
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BmpFormat {
+    pub depth: BmpDepth,
+    pub top_down: bool, // Primera fila arriba (altura negativa en la cabecera)
+}
+
+impl Default for BmpFormat {
+    fn default() -> Self {
+        BmpFormat { depth: BmpDepth::Argb32, top_down: false }
+    }
+}
+
+// Imagen leída de un BMP con píxeles 0xAARRGGBB, como el buffer del framebuffer
+pub struct BmpImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u32>,
+}
 
 pub fn write_bmp_file(
     file_path: &str,
     buffer: &[u32],
     width: usize,
     height: usize,
+    format: BmpFormat,
 ) -> Result<()> {
     let file = File::create(file_path)?;
     let mut writer = BufWriter::new(file);
 
-    write_bmp_header(&mut writer, width, height)?;
-    write_pixel_data(&mut writer, buffer, width, height)?;
+    write_bmp(&mut writer, buffer, width, height, format)?;
 
     writer.flush()?;
     Ok(())
 }
 
-fn write_bmp_header(
-    writer: &mut BufWriter<File>,
+pub fn write_bmp<W: Write>(
+    writer: &mut W,
+    buffer: &[u32],
     width: usize,
     height: usize,
+    format: BmpFormat,
 ) -> Result<()> {
-    let file_size = (BMP_HEADER_SIZE + (width * height * 4)) as u32;
+    if buffer.len() != width * height {
+        return Err(invalid_input("pixel count does not match image size"));
+    }
+
+    write_bmp_header(writer, width, height, format)?;
+    write_pixel_data(writer, buffer, width, height, format)
+}
+
+pub fn read_bmp_file(file_path: &str) -> Result<BmpImage> {
+    let file = File::open(file_path)?;
+    read_bmp(&mut BufReader::new(file))
+}
+
+// Lee BMP de 24 y 32 bits, de abajo hacia arriba o de arriba hacia abajo, sin comprimir o con BI_BITFIELDS
+pub fn read_bmp<R: Read>(reader: &mut R) -> Result<BmpImage> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+
+    if data.len() < BMP_PIXEL_OFFSET || &data[0..2] != b"BM" {
+        return Err(invalid_data("not a BMP file"));
+    }
+
+    let pixel_offset = read_u32(&data, 10)? as usize;
+    let header_size = read_u32(&data, 14)? as usize;
+    if header_size < INFO_HEADER_SIZE {
+        return Err(invalid_data("unsupported BMP header"));
+    }
+
+    let width = read_u32(&data, 18)? as i32;
+    let height = read_u32(&data, 22)? as i32;
+    let bits_per_pixel = read_u16(&data, 28)?;
+    let compression = read_u32(&data, 30)?;
+
+    if width <= 0 || height == 0 {
+        return Err(invalid_data("invalid BMP size"));
+    }
+    let top_down = height < 0;
+    let (width, height) = (width as usize, height.unsigned_abs() as usize);
+
+    // Máscaras rojo, verde, azul y alfa
+    let masks = match (compression, bits_per_pixel) {
+        (BI_RGB, 24) => [0x00FF_0000, 0x0000_FF00, 0x0000_00FF, 0],
+        (BI_RGB, 32) => [0x00FF_0000, 0x0000_FF00, 0x0000_00FF, 0xFF00_0000],
+        (BI_BITFIELDS | BI_ALPHABITFIELDS, 32) => {
+            // En cabeceras V4/V5 las máscaras van dentro de la cabecera; en la de 40 bytes, justo después
+            let offset = FILE_HEADER_SIZE + INFO_HEADER_SIZE;
+            let has_alpha = compression == BI_ALPHABITFIELDS || header_size > INFO_HEADER_SIZE + 12;
+            [
+                read_u32(&data, offset)?,
+                read_u32(&data, offset + 4)?,
+                read_u32(&data, offset + 8)?,
+                if has_alpha { read_u32(&data, offset + 12)? } else { 0 },
+            ]
+        }
+        _ => return Err(invalid_data("unsupported BMP format (only 24/32-bit BI_RGB and BI_BITFIELDS)")),
+    };
+
+    let bytes_per_pixel = bits_per_pixel as usize / 8;
+    let (row_size, end) = pixel_data_size(pixel_offset, width, height, bytes_per_pixel)?;
+    if data.len() < end {
+        return Err(invalid_data("truncated BMP pixel data"));
+    }
+
+    let mut pixels = vec![0u32; width * height];
+    for row in 0..height {
+        let y = if top_down { row } else { height - 1 - row };
+        let start = pixel_offset + row * row_size;
+
+        for x in 0..width {
+            let bytes = &data[start + x * bytes_per_pixel..start + (x + 1) * bytes_per_pixel];
+            let value = bytes.iter().rev().fold(0u32, |value, &byte| (value << 8) | byte as u32);
+
+            let [r, g, b, a] = masks.map(|mask| extract(value, mask));
+            let a = if masks[3] == 0 { 0xFF } else { a };
+            pixels[y * width + x] = (a << 24) | (r << 16) | (g << 8) | b;
+        }
+    }
+
+    // Muchos programas escriben BMP de 32 bits con el byte de alfa en cero: se tratan como opacos
+    if bits_per_pixel == 32 && compression == BI_RGB && pixels.iter().all(|pixel| pixel >> 24 == 0) {
+        pixels.iter_mut().for_each(|pixel| *pixel |= 0xFF00_0000);
+    }
+
+    Ok(BmpImage { width, height, pixels })
+}
+
+// Bytes por fila (con relleno a 4) y fin de los píxeles en el archivo. Los valores salen de la cabecera,
+// que puede estar dañada, así que se calculan sin desbordar.
+fn pixel_data_size(pixel_offset: usize, width: usize, height: usize, bytes_per_pixel: usize) -> Result<(usize, usize)> {
+    let row_size = width
+        .checked_mul(bytes_per_pixel)
+        .and_then(|bytes| bytes.checked_next_multiple_of(4))
+        .ok_or_else(|| invalid_data("BMP size too large"))?;
+    let end = row_size
+        .checked_mul(height)
+        .and_then(|size| size.checked_add(pixel_offset))
+        .ok_or_else(|| invalid_data("BMP size too large"))?;
+
+    Ok((row_size, end))
+}
+
+fn write_bmp_header<W: Write>(
+    writer: &mut W,
+    width: usize,
+    height: usize,
+    format: BmpFormat,
+) -> Result<()> {
+    let image_size = (row_size(width, format.depth) * height) as u32;
+    let file_size = BMP_PIXEL_OFFSET as u32 + image_size;
     let reserved: u32 = 0;
     let offset: u32 = BMP_PIXEL_OFFSET as u32;
 
@@ -39,19 +179,21 @@ fn write_bmp_header(
     writer.write_all(&offset.to_le_bytes())?;
 
     // DIB header
-    let header_size: u32 = 40;
+    let header_size: u32 = INFO_HEADER_SIZE as u32;
     let planes: u16 = 1;
-    let bpp: u16 = BMP_BITS_PER_PIXEL as u16;
-    let compression: u32 = 0;
-    let image_size: u32 = (width * height * 4) as u32;
+    let bpp: u16 = match format.depth {
+        BmpDepth::Rgb24 => 24,
+        BmpDepth::Argb32 => 32,
+    };
+    let height = if format.top_down { -(height as i32) } else { height as i32 };
     let ppm: u32 = 2835; // 72 DPI
 
     writer.write_all(&header_size.to_le_bytes())?;
     writer.write_all(&(width as u32).to_le_bytes())?;
-    writer.write_all(&(height as u32).to_le_bytes())?;
+    writer.write_all(&height.to_le_bytes())?;
     writer.write_all(&planes.to_le_bytes())?;
     writer.write_all(&bpp.to_le_bytes())?;
-    writer.write_all(&compression.to_le_bytes())?;
+    writer.write_all(&BI_RGB.to_le_bytes())?;
     writer.write_all(&image_size.to_le_bytes())?;
     writer.write_all(&ppm.to_le_bytes())?;
     writer.write_all(&ppm.to_le_bytes())?;
@@ -61,21 +203,197 @@ fn write_bmp_header(
     Ok(())
 }
 
-fn write_pixel_data(
-    writer: &mut BufWriter<File>,
+fn write_pixel_data<W: Write>(
+    writer: &mut W,
     buffer: &[u32],
     width: usize,
     height: usize,
+    format: BmpFormat,
 ) -> Result<()> {
-    for y in (0..height).rev() {
+    let mut row = Vec::with_capacity(row_size(width, format.depth));
+
+    for i in 0..height {
+        let y = if format.top_down { i } else { height - 1 - i };
+        row.clear();
+
         for x in 0..width {
             let pixel = buffer[y * width + x];
             let b = (pixel & 0xFF) as u8;
             let g = ((pixel >> 8) & 0xFF) as u8;
             let r = ((pixel >> 16) & 0xFF) as u8;
             let a = ((pixel >> 24) & 0xFF) as u8;
-            writer.write_all(&[b, g, r, a])?;
+            match format.depth {
+                BmpDepth::Rgb24 => row.extend_from_slice(&[b, g, r]),
+                BmpDepth::Argb32 => row.extend_from_slice(&[b, g, r, a]),
+            }
         }
+
+        // Cada fila ocupa un múltiplo de 4 bytes
+        row.resize(row_size(width, format.depth), 0);
+        writer.write_all(&row)?;
     }
     Ok(())
 }
+
+fn row_size(width: usize, depth: BmpDepth) -> usize {
+    match depth {
+        BmpDepth::Rgb24 => (width * 3).div_ceil(4) * 4,
+        BmpDepth::Argb32 => width * 4,
+    }
+}
+
+// Valor de un canal según su máscara, escalado a 8 bits
+fn extract(value: u32, mask: u32) -> u32 {
+    if mask == 0 {
+        return 0;
+    }
+
+    let max = (mask >> mask.trailing_zeros()) as u64;
+    let channel = ((value & mask) >> mask.trailing_zeros()) as u64;
+    ((channel * 255 + max / 2) / max) as u32
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| invalid_data("truncated BMP header"))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or_else(|| invalid_data("truncated BMP header"))
+}
+
+fn invalid_data(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+fn invalid_input(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Patrón con todos los canales distintos por píxel para detectar filas o canales cambiados
+    fn pattern(width: usize, height: usize, alpha: bool) -> Vec<u32> {
+        (0..width * height)
+            .map(|i| {
+                let (x, y) = ((i % width) as u32, (i / width) as u32);
+                let a = if alpha { (x * 40 + y * 7) & 0xFF } else { 0xFF };
+                (a << 24) | ((x * 50) << 16) | ((y * 60) << 8) | ((x + y) * 13)
+            })
+            .collect()
+    }
+
+    fn round_trip(buffer: &[u32], width: usize, height: usize, format: BmpFormat) -> (Vec<u8>, BmpImage) {
+        let mut bytes = Vec::new();
+        write_bmp(&mut bytes, buffer, width, height, format).unwrap();
+        let image = read_bmp(&mut bytes.as_slice()).unwrap();
+        (bytes, image)
+    }
+
+    #[test]
+    fn round_trips_24_bit_bottom_up() {
+        let buffer = pattern(4, 3, false);
+        let (_, image) = round_trip(&buffer, 4, 3, BmpFormat { depth: BmpDepth::Rgb24, top_down: false });
+
+        assert_eq!((image.width, image.height), (4, 3));
+        assert_eq!(image.pixels, buffer);
+    }
+
+    #[test]
+    fn round_trips_24_bit_top_down() {
+        let buffer = pattern(4, 3, false);
+        let (bytes, image) = round_trip(&buffer, 4, 3, BmpFormat { depth: BmpDepth::Rgb24, top_down: true });
+
+        assert_eq!(read_u32(&bytes, 22).unwrap() as i32, -3);
+        assert_eq!(image.pixels, buffer);
+    }
+
+    #[test]
+    fn round_trips_32_bit_alpha_both_orders() {
+        let buffer = pattern(3, 4, true);
+
+        for top_down in [false, true] {
+            let (_, image) = round_trip(&buffer, 3, 4, BmpFormat { depth: BmpDepth::Argb32, top_down });
+            assert_eq!((image.width, image.height), (3, 4));
+            assert_eq!(image.pixels, buffer);
+        }
+    }
+
+    #[test]
+    fn pads_odd_width_24_bit_rows() {
+        // 5 píxeles × 3 bytes = 15, rellenado a 16 bytes por fila
+        let buffer = pattern(5, 2, false);
+        let (bytes, image) = round_trip(&buffer, 5, 2, BmpFormat { depth: BmpDepth::Rgb24, top_down: false });
+
+        assert_eq!(bytes.len(), BMP_PIXEL_OFFSET + 16 * 2);
+        assert_eq!(&bytes[BMP_PIXEL_OFFSET + 15..BMP_PIXEL_OFFSET + 16], &[0]);
+        assert_eq!(image.pixels, buffer);
+    }
+
+    #[test]
+    fn reads_v5_bitfields_with_alpha_mask() {
+        // Cabecera V5 de 124 bytes con máscaras RGBA (rojo en el byte alto)
+        let (width, height) = (2u32, 2i32);
+        let masks = [0xFF00_0000u32, 0x00FF_0000, 0x0000_FF00, 0x0000_00FF];
+        let pixels: [u32; 4] = [0x1020_3040, 0x5060_7080, 0x90A0_B0C0, 0xD0E0_F0FF];
+        let offset = FILE_HEADER_SIZE as u32 + 124;
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"BM");
+        bytes.extend_from_slice(&(offset + 16).to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.extend_from_slice(&offset.to_le_bytes());
+        bytes.extend_from_slice(&124u32.to_le_bytes());
+        bytes.extend_from_slice(&width.to_le_bytes());
+        bytes.extend_from_slice(&height.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&32u16.to_le_bytes());
+        bytes.extend_from_slice(&BI_BITFIELDS.to_le_bytes());
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&[0; 16]);
+        masks.iter().for_each(|mask| bytes.extend_from_slice(&mask.to_le_bytes()));
+        bytes.resize(offset as usize, 0);
+        pixels.iter().for_each(|pixel| bytes.extend_from_slice(&pixel.to_le_bytes()));
+
+        let image = read_bmp(&mut bytes.as_slice()).unwrap();
+
+        // De abajo hacia arriba: la primera fila del archivo es la de abajo
+        assert_eq!((image.width, image.height), (2, 2));
+        assert_eq!(image.pixels, vec![0xC090_A0B0, 0xFFD0_E0F0, 0x4010_2030, 0x8050_6070]);
+    }
+
+    #[test]
+    fn rejects_truncated_pixel_data() {
+        let buffer = pattern(4, 4, true);
+        let mut bytes = Vec::new();
+        write_bmp(&mut bytes, &buffer, 4, 4, BmpFormat::default()).unwrap();
+        bytes.truncate(bytes.len() - 1);
+
+        let err = read_bmp(&mut bytes.as_slice()).err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "truncated BMP pixel data");
+    }
+
+    #[test]
+    fn rejects_a_huge_declared_height() {
+        let mut bytes = Vec::new();
+        write_bmp(&mut bytes, &pattern(2, 2, true), 2, 2, BmpFormat::default()).unwrap();
+        bytes[18..22].copy_from_slice(&i32::MAX.to_le_bytes());
+        bytes[22..26].copy_from_slice(&i32::MIN.to_le_bytes());
+
+        let err = read_bmp(&mut bytes.as_slice()).err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        // Con `usize` de 64 bits la cabecera no alcanza a desbordar; el cálculo se prueba directamente
+        let err = pixel_data_size(54, 4, usize::MAX / 8, 4).err().unwrap();
+        assert_eq!(err.to_string(), "BMP size too large");
+        let err = pixel_data_size(usize::MAX - 8, 4, 1, 4).err().unwrap();
+        assert_eq!(err.to_string(), "BMP size too large");
+        assert_eq!(pixel_data_size(54, 3, 2, 3).unwrap(), (12, 78));
+    }
+}
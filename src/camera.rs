@@ -9,11 +9,18 @@ pub struct Camera {
 }
 
 impl Camera {
-    pub fn basis_change(&self, vector: &Vec3) -> Vec3 {
+    // Ejes de la cámara en espacio del mundo: (derecha, arriba, adelante)
+    pub fn basis(&self) -> (Vec3, Vec3, Vec3) {
         let forward = (self.center - self.eye).normalize();
         let right = forward.cross(&self.up).normalize();
         let up = right.cross(&forward).normalize();
 
+        (right, up, forward)
+    }
+
+    pub fn basis_change(&self, vector: &Vec3) -> Vec3 {
+        let (right, up, forward) = self.basis();
+
         // Convertir la dirección del rayo del espacio de la cámara al espacio del mundo
         let rotated = vector.x * right + vector.y * up - vector.z * forward;
 
@@ -0,0 +1,119 @@
+// catalog.rs
+
+use nalgebra_glm::Vec3;
+use std::fs;
+use crate::color::{Color, LinearColor};
+use crate::skybox::Star;
+
+// Magnitud de la estrella más brillante del cielo (Sirio), usada para escalar el brillo
+const BRIGHTEST_MAGNITUDE: f32 = -1.5;
+
+// Carga un catálogo estelar en formato CSV estilo HYG. Se usan las columnas
+// `ra` (horas), `dec` (grados), `mag` (magnitud aparente) y `ci` (índice B–V).
+// Las estrellas más débiles que `limiting_magnitude` se descartan.
+pub fn load_catalog(path: &str, limiting_magnitude: f32) -> Result<Vec<Star>, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let mut lines = contents.lines();
+
+    let header: Vec<String> = lines
+        .next()
+        .ok_or_else(|| format!("{}: empty catalogue", path))?
+        .split(',')
+        .map(|column| column.trim().trim_matches('"').to_lowercase())
+        .collect();
+
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|c| c == name)
+            .ok_or_else(|| format!("{}: missing `{}` column", path, name))
+    };
+    let ra_column = column("ra")?;
+    let dec_column = column("dec")?;
+    let mag_column = column("mag")?;
+    let ci_column = column("ci").ok();
+
+    let mut stars = Vec::new();
+
+    for line in lines {
+        let fields: Vec<&str> = line.split(',').map(|f| f.trim().trim_matches('"')).collect();
+        let field = |index: usize| fields.get(index).and_then(|f| f.parse::<f32>().ok());
+
+        let (Some(ra), Some(dec), Some(magnitude)) = (field(ra_column), field(dec_column), field(mag_column)) else {
+            continue;
+        };
+
+        // HYG incluye al Sol con magnitud -26.7; se ignora cualquier entrada así de brillante
+        if magnitude > limiting_magnitude || magnitude < BRIGHTEST_MAGNITUDE - 1.0 {
+            continue;
+        }
+
+        let color_index = ci_column.and_then(field).unwrap_or(0.6);
+
+        stars.push(Star::new(
+            equatorial_to_direction(ra, dec),
+            magnitude_to_brightness(magnitude, limiting_magnitude),
+            magnitude_to_size(magnitude),
+            color_index_to_color(color_index),
+        ));
+    }
+
+    Ok(stars)
+}
+
+// Ascensión recta en horas y declinación en grados a una dirección unitaria, con el polo norte
+// celeste en +Y. La ascensión recta crece hacia -Z para que el cielo no quede reflejado visto desde dentro.
+fn equatorial_to_direction(ra_hours: f32, dec_degrees: f32) -> Vec3 {
+    let ra = (ra_hours * 15.0).to_radians();
+    let dec = dec_degrees.to_radians();
+    Vec3::new(dec.cos() * ra.cos(), dec.sin(), -dec.cos() * ra.sin())
+}
+
+// Lineal en magnitud (logarítmica en flujo): la más brillante vale 1.0 y la magnitud límite 0.15
+fn magnitude_to_brightness(magnitude: f32, limiting_magnitude: f32) -> f32 {
+    let range = (limiting_magnitude - BRIGHTEST_MAGNITUDE).max(0.1);
+    let t = ((magnitude - BRIGHTEST_MAGNITUDE) / range).clamp(0.0, 1.0);
+    1.0 - 0.85 * t
+}
+
+fn magnitude_to_size(magnitude: f32) -> u8 {
+    if magnitude < 0.5 {
+        3
+    } else if magnitude < 2.0 {
+        2
+    } else {
+        1
+    }
+}
+
+// Color aproximado de una estrella según su índice B–V
+fn color_index_to_color(color_index: f32) -> Color {
+    const TABLE: [(f32, (i32, i32, i32)); 7] = [
+        (-0.33, (155, 176, 255)),
+        (0.0, (202, 216, 255)),
+        (0.3, (248, 247, 255)),
+        (0.6, (255, 244, 234)),
+        (0.9, (255, 228, 196)),
+        (1.4, (255, 204, 142)),
+        (2.0, (255, 170, 100)),
+    ];
+
+    let to_color = |(r, g, b): (i32, i32, i32)| Color::new(r, g, b);
+
+    if color_index <= TABLE[0].0 {
+        return to_color(TABLE[0].1);
+    }
+
+    for pair in TABLE.windows(2) {
+        let (low, low_color) = pair[0];
+        let (high, high_color) = pair[1];
+        if color_index <= high {
+            let t = (color_index - low) / (high - low);
+            // Interpolación en espacio lineal para que los tonos intermedios no se oscurezcan
+            let low = LinearColor::from_srgb(to_color(low_color));
+            return low.lerp(&LinearColor::from_srgb(to_color(high_color)), t).to_srgb();
+        }
+    }
+
+    to_color(TABLE[TABLE.len() - 1].1)
+}
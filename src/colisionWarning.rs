@@ -1,7 +1,9 @@
 use crate::Color;
 use crate::framebuffer::Framebuffer;
 use crate::polygon::Polygon;
+use crate::rect::Rect;
 use nalgebra_glm::Vec2;
+use crate::text::{TextAlign, TextStyle};
 
 pub struct ColisionWarning {
     big_msg: String,
@@ -25,39 +27,40 @@ impl ColisionWarning {
         }
     }
 
-    pub fn render(&mut self, framebuffer: &mut Framebuffer) {
-        let mut vertex = Vec::new();
-        let mut vertex_back = Vec::new();
-
-        vertex.push([(framebuffer.width / 2) as isize - (framebuffer.width / 8) as isize, (framebuffer.height / 2)  as isize - (framebuffer.height / 16) as isize]); //First vertex
-        vertex.push([(framebuffer.width / 2) as isize + (framebuffer.width / 8) as isize, (framebuffer.height / 2)  as isize - (framebuffer.height / 16) as isize]); //Second vertex
-        vertex.push([(framebuffer.width / 2) as isize + (framebuffer.width / 8) as isize, (framebuffer.height / 2)  as isize + (framebuffer.height / 16) as isize]); //Third vertex
-        vertex.push([(framebuffer.width / 2) as isize - (framebuffer.width / 8) as isize, (framebuffer.height / 2)  as isize + (framebuffer.height / 16) as isize]); //Fourth vertex
-        
-        let margin = 2; // Desplazamiento uniforme para agrandar
-vertex_back.push([
-    (framebuffer.width / 2) as isize - (framebuffer.width / 8 + margin) as isize, 
-    (framebuffer.height / 2) as isize - (framebuffer.height / 16 + margin) as isize
-]);
-vertex_back.push([
-    (framebuffer.width / 2) as isize + (framebuffer.width / 8 + margin) as isize, 
-    (framebuffer.height / 2) as isize - (framebuffer.height / 16 + margin) as isize
-]);
-vertex_back.push([
-    (framebuffer.width / 2) as isize + (framebuffer.width / 8 + margin) as isize, 
-    (framebuffer.height / 2) as isize + (framebuffer.height / 16 + margin) as isize
-]);
-vertex_back.push([
-    (framebuffer.width / 2) as isize - (framebuffer.width / 8 + margin) as isize, 
-    (framebuffer.height / 2) as isize + (framebuffer.height / 16 + margin) as isize
-]);
-
-
-        framebuffer.draw_text(framebuffer.width / 2 - 55, framebuffer.height / 2 - 25, &self.big_msg, Color::new(255,255,255), 40.0);
-        framebuffer.draw_text(framebuffer.width / 2 - 107, framebuffer.height / 2 + 20, &self.small_msg, Color::new(255,255,255), 20.0);
-        framebuffer.polygon(&vertex, self.color_warning, self.color_warning);
+    // Centrado en `area`, la vista de la pantalla a la que corresponde la advertencia
+    pub fn render(&mut self, framebuffer: &mut Framebuffer, area: &Rect) {
+        let center_x = area.x + (area.width / 2) as isize;
+        let center_y = area.y + (area.height / 2) as isize;
+        let center = Vec2::new(center_x as f32, center_y as f32);
+        let (half_width, half_height) = ((area.width / 8) as f32, (area.height / 16) as f32);
+
+        let corners = |margin: f32| vec![
+            center + Vec2::new(-half_width - margin, -half_height - margin),
+            center + Vec2::new(half_width + margin, -half_height - margin),
+            center + Vec2::new(half_width + margin, half_height + margin),
+            center + Vec2::new(-half_width - margin, half_height + margin),
+        ];
+        let vertex = corners(0.0);
+        let vertex_back = corners(2.0); // Desplazamiento uniforme para agrandar
+
+        // El HUD se dibuja en orden: primero el borde y encima el recuadro
         framebuffer.polygon(&vertex_back, self.color_back, self.color_back); 
+        framebuffer.polygon(&vertex, self.color_warning, self.color_warning);
+
+        // Ambos mensajes centrados como un bloque dentro del recuadro
+        let (_, big_height) = framebuffer.measure_text(&self.big_msg, 40.0);
+        let (_, small_height) = framebuffer.measure_text(&self.small_msg, 20.0);
+        let top = center_y as f32 - (big_height + small_height) / 2.0;
 
+        // Contorno y sombra oscuros para que el aviso se lea aunque detrás haya un planeta brillante
+        let big_style = TextStyle::new(Color::new(255,255,255))
+            .with_outline(2, Color::new(0,0,0))
+            .with_shadow(3, 3, Color::new(0,0,0), 0.6);
+        let small_style = TextStyle::new(Color::new(255,255,255))
+            .with_outline(1, Color::new(0,0,0))
+            .with_shadow(2, 2, Color::new(0,0,0), 0.6);
 
+        framebuffer.draw_text_styled(center_x as f32, top, &self.big_msg, &big_style, 40.0, TextAlign::Center);
+        framebuffer.draw_text_styled(center_x as f32, top + big_height, &self.small_msg, &small_style, 20.0, TextAlign::Center);
     }
 }
@@ -1,6 +1,8 @@
 // color.rs
 
 use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Color {
@@ -25,17 +27,9 @@ impl Color {
         Color::new(r.into(), g.into(), b.into())
     }
 
+    // Acepta `#RRGGBB`, `#RRGGBBAA` (el alfa se descarta) o un nombre como `orange`
     pub fn from_hex_str(hex_str: &str) -> Result<Color, String> {
-        let hex_str = hex_str.trim_start_matches('#');
-
-        if hex_str.len() != 6 {
-            return Err("Hex string must be exactly 6 characters long".to_string());
-        }
-
-        let hex = u32::from_str_radix(hex_str, 16)
-            .map_err(|_| "Failed to parse hex string".to_string())?;
-
-        Ok(Color::from_hex(hex))
+        parse_rgba(hex_str).map(|(color, _)| color)
     }
 
     pub fn to_hex(&self) -> u32 {
@@ -108,6 +102,412 @@ impl Color {
     pub fn is_equal(&self, other: &Color) -> bool {
         self.r == other.r && self.g == other.g && self.b == other.b
     }
+
+    // Tono en grados [0, 360), saturación y valor en [0, 1]
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let (r, g, b) = self.to_unit();
+        let max = r.max(g).max(b);
+        let chroma = max - r.min(g).min(b);
+        let saturation = if max > 0.0 { chroma / max } else { 0.0 };
+
+        (hue(r, g, b, max, chroma), saturation, max)
+    }
+
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Color {
+        let chroma = value * saturation;
+        Color::from_hue_chroma(hue, chroma, value - chroma)
+    }
+
+    // Tono en grados [0, 360), saturación y luminosidad en [0, 1]
+    pub fn to_hsl(self) -> (f32, f32, f32) {
+        let (r, g, b) = self.to_unit();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let chroma = max - min;
+        let lightness = (max + min) / 2.0;
+        let saturation = if chroma > 0.0 { chroma / (1.0 - (2.0 * lightness - 1.0).abs()) } else { 0.0 };
+
+        (hue(r, g, b, max, chroma), saturation, lightness)
+    }
+
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Color {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        Color::from_hue_chroma(hue, chroma, lightness - chroma / 2.0)
+    }
+
+    // Decodifica a espacio lineal para sombrear; ver `LinearColor`
+    pub fn to_linear(self) -> LinearColor {
+        LinearColor::from_srgb(self)
+    }
+
+    fn to_unit(self) -> (f32, f32, f32) {
+        (self.r as f32 / 255.0, self.g as f32 / 255.0, self.b as f32 / 255.0)
+    }
+
+    fn to_unit_array(self) -> [f32; 3] {
+        let (r, g, b) = self.to_unit();
+        [r, g, b]
+    }
+
+    // Color con el tono y croma dados, más `offset` en los tres canales
+    fn from_hue_chroma(hue: f32, chroma: f32, offset: f32) -> Color {
+        let sector = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+        let (r, g, b) = match sector as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+
+        let channel = |value: f32| ((value + offset) * 255.0).round() as i32;
+        Color::new(channel(r), channel(g), channel(b))
+    }
+}
+
+fn hue(r: f32, g: f32, b: f32, max: f32, chroma: f32) -> f32 {
+    if chroma == 0.0 {
+        return 0.0;
+    }
+
+    let hue = if max == r {
+        (g - b) / chroma
+    } else if max == g {
+        (b - r) / chroma + 2.0
+    } else {
+        (r - g) / chroma + 4.0
+    };
+    (hue * 60.0).rem_euclid(360.0)
+}
+
+// Modos de fusión al estilo de los editores de imágenes (definiciones de W3C Compositing and Blending)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlendMode {
+    Normal,
+    Multiply,
+    Screen,
+    Add,
+    Subtract,
+    Overlay,
+    SoftLight,
+    ColorDodge,
+    ColorBurn,
+    Darken,
+    Lighten,
+    Difference,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+}
+
+impl BlendMode {
+    // Porter–Duff "over" con alfa no premultiplicado: la fuente se fusiona con el fondo según el modo
+    // donde ambos se cubren. Devuelve el color y el alfa resultantes.
+    pub fn composite(&self, backdrop: Color, backdrop_alpha: f32, source: Color, source_alpha: f32) -> (Color, f32) {
+        let source_alpha = source_alpha.clamp(0.0, 1.0);
+        let backdrop_alpha = backdrop_alpha.clamp(0.0, 1.0);
+        let out_alpha = source_alpha + backdrop_alpha * (1.0 - source_alpha);
+        if out_alpha <= 0.0 {
+            return (backdrop, 0.0);
+        }
+
+        let [r, g, b] = self.composite_unit(backdrop.to_unit_array(), backdrop_alpha, source.to_unit_array(), source_alpha, out_alpha);
+        let channel = |value: f32| (value * 255.0).round() as i32;
+
+        (Color::new(channel(r), channel(g), channel(b)), out_alpha)
+    }
+
+    // Como `composite`, con canales en [0, 1] y el alfa resultante ya calculado
+    fn composite_unit(&self, cb: [f32; 3], backdrop_alpha: f32, cs: [f32; 3], source_alpha: f32, out_alpha: f32) -> [f32; 3] {
+        let mixed = self.mix(cb, cs);
+
+        std::array::from_fn(|i| {
+            let source = (1.0 - backdrop_alpha) * cs[i] + backdrop_alpha * mixed[i];
+            (source_alpha * source + backdrop_alpha * (1.0 - source_alpha) * cb[i]) / out_alpha
+        })
+    }
+
+    // Modos cuya fórmula sólo tiene sentido con canales en [0, 1] (raíces y divisiones entre 1 - c)
+    fn needs_unit_range(&self) -> bool {
+        matches!(self, BlendMode::SoftLight | BlendMode::ColorDodge | BlendMode::ColorBurn)
+    }
+
+    // Función de fusión B(Cb, Cs) con canales en [0, 1]
+    fn mix(&self, cb: [f32; 3], cs: [f32; 3]) -> [f32; 3] {
+        let separable = |f: fn(f32, f32) -> f32| [f(cb[0], cs[0]), f(cb[1], cs[1]), f(cb[2], cs[2])];
+
+        match self {
+            BlendMode::Normal => cs,
+            BlendMode::Multiply => separable(|b, s| b * s),
+            BlendMode::Screen => separable(screen),
+            BlendMode::Add => separable(|b, s| (b + s).min(1.0)),
+            BlendMode::Subtract => separable(|b, s| (b - s).max(0.0)),
+            BlendMode::Overlay => separable(|b, s| hard_light(s, b)),
+            BlendMode::SoftLight => separable(soft_light),
+            BlendMode::ColorDodge => separable(|b, s| {
+                if b == 0.0 { 0.0 } else if s >= 1.0 { 1.0 } else { (b / (1.0 - s)).min(1.0) }
+            }),
+            BlendMode::ColorBurn => separable(|b, s| {
+                if b >= 1.0 { 1.0 } else if s == 0.0 { 0.0 } else { 1.0 - ((1.0 - b) / s).min(1.0) }
+            }),
+            BlendMode::Darken => separable(f32::min),
+            BlendMode::Lighten => separable(f32::max),
+            BlendMode::Difference => separable(|b, s| (b - s).abs()),
+            BlendMode::Hue => set_luminosity(set_saturation(cs, saturation(cb)), luminosity(cb)),
+            BlendMode::Saturation => set_luminosity(set_saturation(cb, saturation(cs)), luminosity(cb)),
+            BlendMode::Color => set_luminosity(cs, luminosity(cb)),
+            BlendMode::Luminosity => set_luminosity(cb, luminosity(cs)),
+        }
+    }
+}
+
+impl FromStr for BlendMode {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, String> {
+        Ok(match name.trim() {
+            "normal" => BlendMode::Normal,
+            "multiply" => BlendMode::Multiply,
+            "screen" => BlendMode::Screen,
+            "add" => BlendMode::Add,
+            "subtract" => BlendMode::Subtract,
+            "overlay" => BlendMode::Overlay,
+            "soft-light" => BlendMode::SoftLight,
+            "color-dodge" => BlendMode::ColorDodge,
+            "color-burn" => BlendMode::ColorBurn,
+            "darken" => BlendMode::Darken,
+            "lighten" => BlendMode::Lighten,
+            "difference" => BlendMode::Difference,
+            "hue" => BlendMode::Hue,
+            "saturation" => BlendMode::Saturation,
+            "color" => BlendMode::Color,
+            "luminosity" => BlendMode::Luminosity,
+            _ => return Err(format!("unknown blend mode `{}`", name.trim())),
+        })
+    }
+}
+
+fn screen(b: f32, s: f32) -> f32 {
+    b + s - b * s
+}
+
+fn hard_light(b: f32, s: f32) -> f32 {
+    if s <= 0.5 {
+        b * 2.0 * s
+    } else {
+        screen(b, 2.0 * s - 1.0)
+    }
+}
+
+fn soft_light(b: f32, s: f32) -> f32 {
+    if s <= 0.5 {
+        b - (1.0 - 2.0 * s) * b * (1.0 - b)
+    } else {
+        let d = if b <= 0.25 { ((16.0 * b - 12.0) * b + 4.0) * b } else { b.sqrt() };
+        b + (2.0 * s - 1.0) * (d - b)
+    }
+}
+
+fn luminosity(c: [f32; 3]) -> f32 {
+    0.3 * c[0] + 0.59 * c[1] + 0.11 * c[2]
+}
+
+fn saturation(c: [f32; 3]) -> f32 {
+    c[0].max(c[1]).max(c[2]) - c[0].min(c[1]).min(c[2])
+}
+
+// Desplaza el color a la luminosidad dada y lo devuelve al rango [0, 1] sin cambiar su tono
+fn set_luminosity(c: [f32; 3], target: f32) -> [f32; 3] {
+    let d = target - luminosity(c);
+    let c = c.map(|channel| channel + d);
+
+    let l = luminosity(c);
+    let min = c[0].min(c[1]).min(c[2]);
+    let max = c[0].max(c[1]).max(c[2]);
+    c.map(|channel| {
+        let mut channel = channel;
+        if min < 0.0 {
+            channel = l + (channel - l) * l / (l - min);
+        }
+        if max > 1.0 {
+            channel = l + (channel - l) * (1.0 - l) / (max - l);
+        }
+        channel
+    })
+}
+
+fn set_saturation(c: [f32; 3], target: f32) -> [f32; 3] {
+    let max = c[0].max(c[1]).max(c[2]);
+    let min = c[0].min(c[1]).min(c[2]);
+    if max <= min {
+        return [0.0; 3];
+    }
+
+    c.map(|channel| (channel - min) * target / (max - min))
+}
+
+// Colores con nombre que se aceptan al leer colores de texto
+const NAMED_COLORS: [(&str, u32); 20] = [
+    ("black", 0x000000FF),
+    ("white", 0xFFFFFFFF),
+    ("red", 0xFF0000FF),
+    ("lime", 0x00FF00FF),
+    ("green", 0x008000FF),
+    ("blue", 0x0000FFFF),
+    ("yellow", 0xFFFF00FF),
+    ("cyan", 0x00FFFFFF),
+    ("aqua", 0x00FFFFFF),
+    ("magenta", 0xFF00FFFF),
+    ("fuchsia", 0xFF00FFFF),
+    ("gray", 0x808080FF),
+    ("grey", 0x808080FF),
+    ("silver", 0xC0C0C0FF),
+    ("maroon", 0x800000FF),
+    ("olive", 0x808000FF),
+    ("navy", 0x000080FF),
+    ("purple", 0x800080FF),
+    ("orange", 0xFFA500FF),
+    ("transparent", 0x00000000),
+];
+
+// Color y alfa (0 a 255) de `#RRGGBB`, `#RRGGBBAA` o un nombre de `NAMED_COLORS`
+fn parse_rgba(text: &str) -> Result<(Color, u8), String> {
+    let text = text.trim();
+
+    let rgba = match NAMED_COLORS.iter().find(|(name, _)| name.eq_ignore_ascii_case(text)) {
+        Some(&(_, rgba)) => rgba,
+        None => {
+            let hex_str = text.trim_start_matches('#');
+            let hex = u32::from_str_radix(hex_str, 16).map_err(|_| format!("Failed to parse color `{}`", text))?;
+            match hex_str.len() {
+                6 => (hex << 8) | 0xFF,
+                8 => hex,
+                _ => return Err("Hex string must be 6 or 8 characters long".to_string()),
+            }
+        }
+    };
+
+    Ok((Color::from_hex(rgba >> 8), (rgba & 0xFF) as u8))
+}
+
+// Color en espacio lineal con alfa para hacer la matemática de iluminación y mezcla.
+// Los canales pueden pasar de 1 (HDR); se codifican a sRGB al convertir a `Color`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearColor {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl LinearColor {
+    pub fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        LinearColor { r, g, b, a }
+    }
+
+    // Decodifica un color sRGB opaco
+    pub fn from_srgb(color: Color) -> Self {
+        let table = srgb_table();
+        LinearColor::new(table[color.r as usize], table[color.g as usize], table[color.b as usize], 1.0)
+    }
+
+    // Codifica a sRGB limitando a [0, 1]; el alfa se descarta
+    pub fn to_srgb(self) -> Color {
+        let channel = |value: f32| (linear_to_srgb(value) * 255.0).round() as i32;
+        Color::new(channel(self.r), channel(self.g), channel(self.b))
+    }
+
+    pub fn lerp(&self, other: &LinearColor, t: f32) -> LinearColor {
+        *self * (1.0 - t) + *other * t
+    }
+
+    pub fn is_black(&self) -> bool {
+        self.r <= 0.0 && self.g <= 0.0 && self.b <= 0.0
+    }
+
+    // Compone `source` con opacidad `alpha` sobre este color (opaco) usando `mode`, en espacio lineal.
+    // Los canales sólo se limitan a [0, 1] en los modos cuyas fórmulas lo requieren; el resto conserva el HDR.
+    pub fn blend(&self, source: &LinearColor, mode: BlendMode, alpha: f32) -> LinearColor {
+        let channels = |color: &LinearColor| {
+            let rgb = [color.r, color.g, color.b];
+            if mode.needs_unit_range() { rgb.map(|channel| channel.clamp(0.0, 1.0)) } else { rgb }
+        };
+        let alpha = alpha.clamp(0.0, 1.0);
+        let [r, g, b] = mode.composite_unit(channels(self), 1.0, channels(source), alpha, 1.0);
+
+        LinearColor::new(r, g, b, self.a)
+    }
+}
+
+impl FromStr for LinearColor {
+    type Err = String;
+
+    // Igual que `Color::from_hex_str`, pero conserva el alfa de `#RRGGBBAA`
+    fn from_str(text: &str) -> Result<Self, String> {
+        let (color, alpha) = parse_rgba(text)?;
+        Ok(LinearColor { a: alpha as f32 / 255.0, ..LinearColor::from_srgb(color) })
+    }
+}
+
+impl std::ops::Add for LinearColor {
+    type Output = LinearColor;
+
+    fn add(self, other: LinearColor) -> LinearColor {
+        LinearColor::new(self.r + other.r, self.g + other.g, self.b + other.b, self.a + other.a)
+    }
+}
+
+impl std::ops::Mul<f32> for LinearColor {
+    type Output = LinearColor;
+
+    fn mul(self, factor: f32) -> LinearColor {
+        LinearColor::new(self.r * factor, self.g * factor, self.b * factor, self.a * factor)
+    }
+}
+
+impl std::ops::Mul for LinearColor {
+    type Output = LinearColor;
+
+    fn mul(self, other: LinearColor) -> LinearColor {
+        LinearColor::new(self.r * other.r, self.g * other.g, self.b * other.b, self.a * other.a)
+    }
+}
+
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(value: f32) -> f32 {
+    let value = value.clamp(0.0, 1.0);
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+// Canales rojo, verde y azul (0 a 255) de un píxel 0xRRGGBB del framebuffer
+pub fn channels(pixel: u32) -> [f32; 3] {
+    [((pixel >> 16) & 0xFF) as f32, ((pixel >> 8) & 0xFF) as f32, (pixel & 0xFF) as f32]
+}
+
+// Inverso de `channels`: redondea y limita cada canal a 0..=255
+pub fn pack([r, g, b]: [f32; 3]) -> u32 {
+    let channel = |value: f32| value.round().clamp(0.0, 255.0) as u32;
+    (channel(r) << 16) | (channel(g) << 8) | channel(b)
+}
+
+// Valores lineales de los 256 niveles sRGB, calculados una sola vez
+fn srgb_table() -> &'static [f32; 256] {
+    static TABLE: OnceLock<[f32; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| std::array::from_fn(|level| srgb_to_linear(level as f32 / 255.0)))
 }
 
 impl fmt::Display for Color {
@@ -148,3 +548,193 @@ impl std::ops::Sub for Color {
         Color::new(r.into(), g.into(), b.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Colores de prueba que recorren el cubo RGB en pasos de 51
+    fn samples() -> impl Iterator<Item = Color> {
+        (0..216).map(|i| Color::new(i % 6 * 51, i / 6 % 6 * 51, i / 36 * 51))
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-4, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn srgb_levels_survive_a_round_trip_through_linear() {
+        for level in 0..=255 {
+            let color = Color::new(level, level, level);
+            assert!(color.to_linear().to_srgb().is_equal(&color), "level {}", level);
+        }
+
+        assert_close(srgb_to_linear(0.5), 0.21404);
+        assert_close(linear_to_srgb(0.21404), 0.5);
+        // El tramo lineal cerca del negro
+        assert_close(srgb_to_linear(0.04), 0.04 / 12.92);
+        assert_close(linear_to_srgb(0.002), 0.002 * 12.92);
+    }
+
+    #[test]
+    fn encoding_limits_hdr_channels() {
+        let hdr = LinearColor::new(4.0, -1.0, 0.5, 1.0);
+        assert!(hdr.to_srgb().is_equal(&Color::new(255, 0, 188)));
+    }
+
+    #[test]
+    fn parses_hex_with_alpha_and_named_colors() {
+        let (color, alpha) = parse_rgba("#FF800040").unwrap();
+        assert!(color.is_equal(&Color::new(255, 128, 0)));
+        assert_eq!(alpha, 0x40);
+
+        let (color, alpha) = parse_rgba(" 00ff00 ").unwrap();
+        assert!(color.is_equal(&Color::new(0, 255, 0)));
+        assert_eq!(alpha, 255);
+
+        // Los nombres no distinguen mayúsculas
+        let (color, alpha) = parse_rgba("Orange").unwrap();
+        assert_eq!(color.to_hex(), 0xFFA500);
+        assert_eq!(alpha, 255);
+        assert_eq!(parse_rgba("transparent").unwrap().1, 0);
+
+        assert!(parse_rgba("#FF80").is_err());
+        assert!(parse_rgba("#GG0000").is_err());
+        assert!(parse_rgba("chartreuse").is_err());
+
+        // `Color` descarta el alfa
+        assert_eq!(Color::from_hex_str("#12345678").unwrap().to_hex(), 0x123456);
+    }
+
+    #[test]
+    fn linear_color_keeps_the_parsed_alpha() {
+        let color: LinearColor = "#FFFFFF80".parse().unwrap();
+        assert_eq!(color.r, 1.0);
+        assert_close(color.a, 128.0 / 255.0);
+
+        let navy: LinearColor = "navy".parse().unwrap();
+        assert_eq!(navy.a, 1.0);
+        assert_eq!(navy.r, 0.0);
+        assert_close(navy.b, srgb_to_linear(128.0 / 255.0));
+
+        assert!("#123".parse::<LinearColor>().is_err());
+    }
+
+    #[test]
+    fn hsv_round_trips() {
+        for color in samples() {
+            let (hue, saturation, value) = color.to_hsv();
+            assert!(Color::from_hsv(hue, saturation, value).is_equal(&color), "{}", color);
+        }
+
+        let (hue, saturation, value) = Color::new(0, 255, 0).to_hsv();
+        assert_eq!((hue, saturation, value), (120.0, 1.0, 1.0));
+        let (_, saturation, value) = Color::new(102, 102, 102).to_hsv();
+        assert_eq!(saturation, 0.0);
+        assert_close(value, 0.4);
+    }
+
+    #[test]
+    fn hsl_round_trips() {
+        for color in samples() {
+            let (hue, saturation, lightness) = color.to_hsl();
+            assert!(Color::from_hsl(hue, saturation, lightness).is_equal(&color), "{}", color);
+        }
+
+        let (hue, saturation, lightness) = Color::new(0, 0, 255).to_hsl();
+        assert_eq!((hue, saturation, lightness), (240.0, 1.0, 0.5));
+        // Un tono fuera de [0, 360) da la vuelta
+        assert!(Color::from_hsl(-120.0, 1.0, 0.5).is_equal(&Color::new(0, 0, 255)));
+    }
+
+    fn assert_mix(mode: BlendMode, cb: [f32; 3], cs: [f32; 3], expected: [f32; 3]) {
+        let mixed = mode.mix(cb, cs);
+        for (actual, expected) in mixed.iter().zip(expected) {
+            assert!((actual - expected).abs() < 1e-5, "{:?}: {:?} != {:?}", mode, mixed, expected);
+        }
+    }
+
+    #[test]
+    fn separable_modes_follow_the_w3c_formulas() {
+        // Canales elegidos para pasar por las dos ramas de overlay, soft light, dodge y burn
+        let cb = [0.2, 0.5, 0.9];
+        let cs = [0.7, 0.3, 0.6];
+
+        assert_mix(BlendMode::Normal, cb, cs, cs);
+        assert_mix(BlendMode::Multiply, cb, cs, [0.14, 0.15, 0.54]);
+        assert_mix(BlendMode::Screen, cb, cs, [0.76, 0.65, 0.96]);
+        assert_mix(BlendMode::Add, cb, cs, [0.9, 0.8, 1.0]);
+        assert_mix(BlendMode::Subtract, cb, cs, [0.0, 0.2, 0.3]);
+        assert_mix(BlendMode::Overlay, cb, cs, [0.28, 0.3, 0.92]);
+        assert_mix(BlendMode::SoftLight, cb, cs, [0.2992, 0.4, 0.909737]);
+        assert_mix(BlendMode::ColorDodge, cb, cs, [0.666667, 0.714286, 1.0]);
+        assert_mix(BlendMode::ColorBurn, cb, cs, [0.0, 0.0, 0.833333]);
+        assert_mix(BlendMode::Darken, cb, cs, [0.2, 0.3, 0.6]);
+        assert_mix(BlendMode::Lighten, cb, cs, [0.7, 0.5, 0.9]);
+        assert_mix(BlendMode::Difference, cb, cs, [0.5, 0.2, 0.3]);
+
+        // Casos límite de dodge y burn
+        assert_mix(BlendMode::ColorDodge, [0.0, 0.5, 0.5], [0.5, 1.0, 0.0], [0.0, 1.0, 0.5]);
+        assert_mix(BlendMode::ColorBurn, [1.0, 0.5, 0.5], [0.5, 0.0, 1.0], [1.0, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn non_separable_modes_follow_the_w3c_formulas() {
+        let cb = [0.2, 0.5, 0.9];
+        let cs = [0.7, 0.3, 0.6];
+
+        assert_mix(BlendMode::Hue, cb, cs, [0.88625, 0.18625, 0.71125]);
+        assert_mix(BlendMode::Saturation, cb, cs, [0.308857, 0.480286, 0.708857]);
+        assert_mix(BlendMode::Color, cb, cs, [0.701, 0.301, 0.601]);
+        assert_mix(BlendMode::Luminosity, cb, cs, [0.199, 0.499, 0.899]);
+
+        // Luminosidades que sacan el color de [0, 1] y obligan a recortarlo (ClipColor)
+        let bright = [0.9, 0.9, 0.1];
+        let blue = [0.1, 0.1, 0.95];
+        assert_mix(BlendMode::Luminosity, bright, blue, [0.217416, 0.217416, 0.0]);
+        assert_mix(BlendMode::Color, bright, blue, [0.788764, 0.788764, 1.0]);
+    }
+
+    #[test]
+    fn over_composites_partial_alpha() {
+        let red = Color::new(255, 0, 0);
+        let blue = Color::new(0, 0, 255);
+
+        // αo = αs + αb(1 - αs); Co = (αs·Cs + αb(1 - αs)·Cb) / αo
+        let (color, alpha) = BlendMode::Normal.composite(blue, 0.5, red, 0.5);
+        assert_close(alpha, 0.75);
+        assert!(color.is_equal(&Color::new(170, 0, 85)), "{}", color);
+
+        // Sobre un fondo transparente queda la fuente con su propio alfa
+        let (color, alpha) = BlendMode::Multiply.composite(blue, 0.0, red, 0.4);
+        assert_close(alpha, 0.4);
+        assert!(color.is_equal(&red));
+
+        // Sobre un fondo opaco el modo sólo actúa en la parte cubierta por la fuente
+        let gray = Color::new(128, 128, 128);
+        let (color, alpha) = BlendMode::Multiply.composite(gray, 1.0, red, 0.5);
+        assert_eq!(alpha, 1.0);
+        assert!(color.is_equal(&Color::new(128, 64, 64)), "{}", color);
+
+        let (_, alpha) = BlendMode::Normal.composite(blue, 0.0, red, 0.0);
+        assert_eq!(alpha, 0.0);
+    }
+
+    #[test]
+    fn linear_blend_keeps_hdr_headroom() {
+        let backdrop = LinearColor::new(2.0, 2.0, 2.0, 1.0);
+        let source = LinearColor::new(4.0, 0.0, 0.5, 1.0);
+
+        let over = backdrop.blend(&source, BlendMode::Normal, 0.5);
+        assert_eq!((over.r, over.g, over.b), (3.0, 1.0, 1.25));
+
+        let multiplied = backdrop.blend(&source, BlendMode::Multiply, 1.0);
+        assert_eq!((multiplied.r, multiplied.g, multiplied.b), (8.0, 0.0, 1.0));
+
+        // Dodge, burn y soft light trabajan en [0, 1]
+        let dodged = backdrop.blend(&source, BlendMode::ColorDodge, 1.0);
+        assert_eq!((dodged.r, dodged.g, dodged.b), (1.0, 1.0, 1.0));
+        let soft = backdrop.blend(&source, BlendMode::SoftLight, 1.0);
+        assert!([soft.r, soft.g, soft.b].iter().all(|channel| (0.0..=1.0).contains(channel)));
+    }
+}
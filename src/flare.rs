@@ -0,0 +1,278 @@
+// flare.rs
+
+use crate::framebuffer::Framebuffer;
+use crate::rect::Rect;
+use crate::render::Uniforms;
+use nalgebra_glm::{Vec3, Vec4};
+
+// Resolución reducida a la que se calculan los rayos de luz
+const RAY_DOWNSCALE: usize = 4;
+const RAY_SAMPLES: usize = 32;
+const RAY_DECAY: f32 = 0.95;
+
+// Muestras por lado de la cuadrícula con que se mide cuánto de la estrella se ve
+const VISIBILITY_GRID: usize = 9;
+
+const FLARE_COLOR: [f32; 3] = [255.0, 225.0, 170.0];
+
+// Reflejos internos de la lente: posición sobre la recta estrella-centro (1 = estrella, -1 = punto opuesto),
+// radio como fracción del alto de la pantalla, color e intensidad
+const GHOSTS: [(f32, f32, [f32; 3], f32); 6] = [
+    (0.55, 0.020, [255.0, 200.0, 120.0], 0.35),
+    (0.25, 0.050, [120.0, 255.0, 160.0], 0.12),
+    (-0.20, 0.030, [140.0, 180.0, 255.0], 0.25),
+    (-0.45, 0.080, [200.0, 140.0, 255.0], 0.10),
+    (-0.75, 0.040, [255.0, 160.0, 110.0], 0.20),
+    (-1.20, 0.130, [110.0, 200.0, 255.0], 0.08),
+];
+
+// Destello de lente y rayos de luz de la estrella, en espacio de pantalla sobre la escena ya renderizada.
+// Planetas y nave tapan la estrella según el z-buffer; el destello se atenúa con la fracción visible.
+pub struct LensFlare {
+    flare_intensity: f32, // 0 desactiva reflejos y estallido
+    ray_intensity: f32,   // 0 desactiva los rayos
+    mask: Vec<f32>,
+    rays: Vec<f32>,
+}
+
+// Estrella proyectada: centro y radio en píxeles, y profundidad a partir de la cual un píxel no la tapa
+struct ProjectedStar {
+    x: f32,
+    y: f32,
+    radius: f32,
+    depth: f32,
+}
+
+impl LensFlare {
+    pub fn new(flare_intensity: f32, ray_intensity: f32) -> Self {
+        LensFlare {
+            flare_intensity,
+            ray_intensity,
+            mask: Vec::new(),
+            rays: Vec::new(),
+        }
+    }
+
+    // `uniforms` deben tener las matrices con que se renderizó `framebuffer`. Se dibuja dentro de su
+    // recorte (la vista actual), con los reflejos alineados con el centro de esa zona.
+    pub fn render(&mut self, framebuffer: &mut Framebuffer, uniforms: &Uniforms, star_position: Vec3, star_radius: f32) {
+        if self.flare_intensity <= 0.0 && self.ray_intensity <= 0.0 {
+            return;
+        }
+        let area = framebuffer.clip_rect();
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        let Some(star) = project_star(uniforms, star_position, star_radius) else {
+            return;
+        };
+
+        // Fuera de la vista el efecto se desvanece a lo largo de medio alto de la vista
+        let (left, top) = (area.x as f32, area.y as f32);
+        let (right, bottom) = (left + area.width as f32, top + area.height as f32);
+        let outside = (left - star.x).max(star.x - right).max(0.0).hypot((top - star.y).max(star.y - bottom).max(0.0));
+        let edge_fade = 1.0 - outside / (0.5 * area.height as f32);
+        if edge_fade <= 0.0 {
+            return;
+        }
+
+        if self.ray_intensity > 0.0 {
+            self.render_rays(framebuffer, &area, &star, self.ray_intensity * edge_fade);
+        }
+
+        let intensity = self.flare_intensity * edge_fade * visibility(framebuffer, &area, &star);
+        if intensity > 0.0 {
+            starburst(framebuffer, &area, &star, intensity);
+            ghosts(framebuffer, &area, &star, intensity);
+        }
+    }
+
+    // Desenfoque radial hacia la estrella de una máscara con la luz que no está tapada
+    fn render_rays(&mut self, framebuffer: &mut Framebuffer, area: &Rect, star: &ProjectedStar, intensity: f32) {
+        let width = area.width.div_ceil(RAY_DOWNSCALE);
+        let height = area.height.div_ceil(RAY_DOWNSCALE);
+        let halo = star.radius * 1.5 + 0.08 * area.height as f32;
+        let (left, top) = (area.x as usize, area.y as usize);
+
+        self.mask.clear();
+        for y in 0..height {
+            for x in 0..width {
+                let px = left + (x * RAY_DOWNSCALE + RAY_DOWNSCALE / 2).min(area.width - 1);
+                let py = top + (y * RAY_DOWNSCALE + RAY_DOWNSCALE / 2).min(area.height - 1);
+                let index = py * framebuffer.width + px;
+
+                let glow = (1.0 - (px as f32 - star.x).hypot(py as f32 - star.y) / halo).max(0.0);
+                let lit = framebuffer.zbuffer[index] >= star.depth;
+                self.mask.push(if lit { glow * glow } else { 0.0 });
+            }
+        }
+
+        let star_x = (star.x - left as f32) / RAY_DOWNSCALE as f32;
+        let star_y = (star.y - top as f32) / RAY_DOWNSCALE as f32;
+        let total_weight: f32 = (0..RAY_SAMPLES).map(|i| RAY_DECAY.powi(i as i32)).sum();
+
+        self.rays.clear();
+        for y in 0..height {
+            for x in 0..width {
+                let (dx, dy) = ((star_x - x as f32) / RAY_SAMPLES as f32, (star_y - y as f32) / RAY_SAMPLES as f32);
+                let (mut sx, mut sy) = (x as f32, y as f32);
+                let (mut sum, mut weight) = (0.0, 1.0);
+
+                for _ in 0..RAY_SAMPLES {
+                    if sx >= 0.0 && sy >= 0.0 && (sx as usize) < width && (sy as usize) < height {
+                        sum += self.mask[sy as usize * width + sx as usize] * weight;
+                    }
+                    weight *= RAY_DECAY;
+                    sx += dx;
+                    sy += dy;
+                }
+                self.rays.push(sum / total_weight);
+            }
+        }
+
+        // Se amplía con interpolación bilineal para que los rayos no se vean en bloques
+        for y in 0..area.height {
+            let fy = ((y as f32 + 0.5) / RAY_DOWNSCALE as f32 - 0.5).clamp(0.0, (height - 1) as f32);
+            let (y0, ty) = (fy as usize, fy.fract());
+            let y1 = (y0 + 1).min(height - 1);
+
+            for x in 0..area.width {
+                let fx = ((x as f32 + 0.5) / RAY_DOWNSCALE as f32 - 0.5).clamp(0.0, (width - 1) as f32);
+                let (x0, tx) = (fx as usize, fx.fract());
+                let x1 = (x0 + 1).min(width - 1);
+
+                let top_row = self.rays[y0 * width + x0] * (1.0 - tx) + self.rays[y0 * width + x1] * tx;
+                let bottom_row = self.rays[y1 * width + x0] * (1.0 - tx) + self.rays[y1 * width + x1] * tx;
+                let value = (top_row * (1.0 - ty) + bottom_row * ty) * intensity;
+                if value > 0.001 {
+                    add(framebuffer, left + x, top + y, FLARE_COLOR, value);
+                }
+            }
+        }
+    }
+}
+
+fn project(uniforms: &Uniforms, point: Vec3) -> Option<Vec3> {
+    let clip = uniforms.projection_matrix * uniforms.view_matrix * Vec4::new(point.x, point.y, point.z, 1.0);
+    if clip.w <= 0.0 {
+        return None;
+    }
+
+    // Igual que el vertex shader, para que la profundidad sea comparable con la del z-buffer
+    let screen = uniforms.viewport_matrix * Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+    Some(Vec3::new(screen.x, screen.y, screen.z))
+}
+
+fn project_star(uniforms: &Uniforms, position: Vec3, radius: f32) -> Option<ProjectedStar> {
+    let to_camera = uniforms.camera_position - position;
+    if to_camera.magnitude() <= radius * 1.1 {
+        return None;
+    }
+
+    let center = project(uniforms, position)?;
+    // Primera fila de la matriz de vista: eje derecho de la cámara en el mundo
+    let right = Vec3::new(uniforms.view_matrix[(0, 0)], uniforms.view_matrix[(0, 1)], uniforms.view_matrix[(0, 2)]);
+    let edge = project(uniforms, position + right * radius)?;
+    // Algo por delante de la superficie, para que las caras del propio modelo no cuenten como obstáculo
+    let front = project(uniforms, position + to_camera.normalize() * radius * 1.1)?;
+
+    Some(ProjectedStar {
+        x: center.x,
+        y: center.y,
+        radius: (edge.x - center.x).hypot(edge.y - center.y).max(1.0),
+        depth: front.z,
+    })
+}
+
+// Fracción del disco de la estrella que no tapa nada. Lo que cae fuera de la vista cuenta como visible.
+fn visibility(framebuffer: &Framebuffer, area: &Rect, star: &ProjectedStar) -> f32 {
+    let (mut visible, mut total) = (0, 0);
+
+    for j in 0..VISIBILITY_GRID {
+        for i in 0..VISIBILITY_GRID {
+            let u = (i as f32 + 0.5) / VISIBILITY_GRID as f32 * 2.0 - 1.0;
+            let v = (j as f32 + 0.5) / VISIBILITY_GRID as f32 * 2.0 - 1.0;
+            if u * u + v * v > 1.0 {
+                continue;
+            }
+
+            total += 1;
+            let (x, y) = ((star.x + u * star.radius).floor() as isize, (star.y + v * star.radius).floor() as isize);
+            if !area.contains(x, y) || framebuffer.zbuffer[y as usize * framebuffer.width + x as usize] >= star.depth {
+                visible += 1;
+            }
+        }
+    }
+
+    visible as f32 / total as f32
+}
+
+// Núcleo brillante con seis puntas finas y seis más tenues entre ellas
+fn starburst(framebuffer: &mut Framebuffer, area: &Rect, star: &ProjectedStar, intensity: f32) {
+    let radius = 0.35 * area.height as f32 * intensity.sqrt();
+    // Las puntas giran un poco al mover la estrella por la pantalla
+    let rotation = (star.x + star.y) * 0.002;
+
+    for_each_in_circle(framebuffer, area, star.x, star.y, radius, |dx, dy, distance| {
+        let d = distance / radius;
+        let angle = dy.atan2(dx) + rotation;
+        let spikes = (angle * 3.0).cos().abs().powi(60) + 0.4 * (angle * 3.0 + std::f32::consts::FRAC_PI_2).cos().abs().powi(120);
+        let core = (-d * 12.0).exp();
+        (core + spikes * (1.0 - d).powi(3)) * intensity
+    }, FLARE_COLOR);
+}
+
+fn ghosts(framebuffer: &mut Framebuffer, area: &Rect, star: &ProjectedStar, intensity: f32) {
+    let center_x = area.x as f32 + area.width as f32 / 2.0;
+    let center_y = area.y as f32 + area.height as f32 / 2.0;
+
+    for (position, size, color, strength) in GHOSTS {
+        let x = center_x + (star.x - center_x) * position;
+        let y = center_y + (star.y - center_y) * position;
+        let radius = size * area.height as f32;
+
+        // Disco suave con el borde algo más brillante, como los reflejos de un diafragma
+        for_each_in_circle(framebuffer, area, x, y, radius, |_, _, distance| {
+            let d = distance / radius;
+            let edge = ((1.0 - d) * 6.0).min(1.0);
+            (0.6 + 0.4 * d * d) * edge * strength * intensity
+        }, color);
+    }
+}
+
+// Suma `color` × el valor de `shade(dx, dy, distancia)` a los píxeles dentro del círculo y de `area`
+fn for_each_in_circle(
+    framebuffer: &mut Framebuffer,
+    area: &Rect,
+    x: f32,
+    y: f32,
+    radius: f32,
+    shade: impl Fn(f32, f32, f32) -> f32,
+    color: [f32; 3],
+) {
+    let min_x = (x - radius).floor().max(area.x as f32) as usize;
+    let max_x = ((x + radius).ceil().max(0.0) as usize).min(area.x as usize + area.width);
+    let min_y = (y - radius).floor().max(area.y as f32) as usize;
+    let max_y = ((y + radius).ceil().max(0.0) as usize).min(area.y as usize + area.height);
+
+    for py in min_y..max_y {
+        for px in min_x..max_x {
+            let (dx, dy) = (px as f32 + 0.5 - x, py as f32 + 0.5 - y);
+            let distance = dx.hypot(dy);
+            if distance < radius {
+                add(framebuffer, px, py, color, shade(dx, dy, distance));
+            }
+        }
+    }
+}
+
+// Mezcla aditiva sobre la escena (0x00RRGGBB); no toca el z-buffer
+fn add(framebuffer: &mut Framebuffer, x: usize, y: usize, color: [f32; 3], amount: f32) {
+    let index = y * framebuffer.width + x;
+    let pixel = framebuffer.buffer[index];
+    let channel = |shift: u32, value: f32| {
+        let sum = ((pixel >> shift) & 0xFF) as f32 + value * amount;
+        (sum.round().min(255.0) as u32) << shift
+    };
+    framebuffer.buffer[index] = channel(16, color[0]) | channel(8, color[1]) | channel(0, color[2]);
+}
@@ -11,6 +11,7 @@ pub struct Fragment {
     pub normal: Vec3,
     pub intensity: f32,
     pub vertex_position: Vec3,
+    pub velocity: Vec2, // Desplazamiento en pantalla desde el cuadro anterior, en píxeles
 }
 
 impl Fragment {
@@ -30,8 +31,14 @@ impl Fragment {
             normal,
             intensity,
             vertex_position,
+            velocity: Vec2::zeros(),
         }
     }
+
+    pub fn with_velocity(mut self, velocity: Vec2) -> Self {
+        self.velocity = velocity;
+        self
+    }
 }
 
 
@@ -1,17 +1,24 @@
 // framebuffer.rs
 
-use crate::color::Color;
-use image::{GenericImageView, Pixel, Rgba};
-use image::imageops::FilterType;
-use rusttype::{Font, Scale, point, PositionedGlyph};
+use crate::color::{BlendMode, Color, LinearColor};
+use crate::sprite::{self, Sprite};
+use crate::texture::Texture;
+use crate::rect::Rect;
+use crate::polygon::{FillRule, Polygon};
+use nalgebra_glm::Vec2;
+use crate::text::{TextAlign, TextRenderer, TextStyle, PlacedGlyph};
 
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
     pub buffer: Vec<u32>, // Para el color de cada píxel
     pub zbuffer: Vec<f32>, // Para la profundidad de cada píxel
+    pub velocity: Vec<Vec2>, // Desplazamiento en pantalla de cada píxel desde el cuadro anterior
     background_color: Color,
     current_color: Color,
+    text: TextRenderer, // Fuente y cache de glifos compartidos por todo el texto del HUD
+    scissor: Option<Rect>, // Recorte de todo el dibujo: figuras, texto, sprites y la escena 3D
+    overlay: bool, // Capa 2D transparente: guarda alfa y dibuja en orden, sin z-buffer
 }
 
 impl Framebuffer {
@@ -28,27 +35,73 @@ impl Framebuffer {
             height,
             buffer,
             zbuffer,
+            velocity: vec![Vec2::zeros(); width * height],
             background_color,
             current_color,
+            text: TextRenderer::default(),
+            scissor: None,
+            overlay: false,
         }
     }
 
+    // Framebuffer transparente para una capa 2D (HUD, menús). El byte alto de cada píxel es el alfa
+    // y todo se dibuja en el orden de las llamadas, sin prueba de profundidad.
+    pub fn new_overlay(width: usize, height: usize) -> Self {
+        let mut framebuffer = Framebuffer::new(width, height);
+        framebuffer.overlay = true;
+        framebuffer.buffer.fill(0);
+        framebuffer
+    }
+
+    pub fn is_overlay(&self) -> bool {
+        self.overlay
+    }
+
+    // Cambia el tamaño conservando el color de fondo, la fuente y el modo; el contenido se descarta
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        self.buffer = vec![0; width * height];
+        self.zbuffer = vec![f32::INFINITY; width * height];
+        self.velocity = vec![Vec2::zeros(); width * height];
+        self.scissor = None;
+        self.clear();
+    }
+
     // Limpiar el framebuffer y el z-buffer
     pub fn clear(&mut self) {
-        let color_hex = self.background_color.to_hex();
+        // Las capas overlay se limpian a transparente
+        let color_hex = if self.overlay { 0 } else { self.background_color.to_hex() };
         self.buffer.fill(color_hex);
         self.zbuffer.fill(f32::INFINITY); // Resetea el z-buffer con valores infinitos
+        self.velocity.fill(Vec2::zeros());
     }
 
     // Dibujar un punto con verificación del z-buffer
     pub fn point(&mut self, x: isize, y: isize, z: f32) {
+        self.point_with_velocity(x, y, z, Vec2::zeros());
+    }
+
+    // Como `point`, guardando además la velocidad en pantalla si el punto queda visible
+    pub fn point_with_velocity(&mut self, x: isize, y: isize, z: f32, velocity: Vec2) {
+        if self.scissor.is_some_and(|scissor| !scissor.contains(x, y)) {
+            return;
+        }
+
         if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
             let index = (self.width * y as usize) + x as usize;
+
+            // En una capa overlay el último en dibujarse queda encima
+            if self.overlay {
+                self.buffer[index] = 0xFF00_0000 | self.current_color.to_hex();
+                return;
+            }
             
             // Verificar si el punto está más cerca que el que ya está en el z-buffer
             if z < self.zbuffer[index] {
                 self.zbuffer[index] = z; // Actualiza el z-buffer con la nueva profundidad
                 self.buffer[index] = self.current_color.to_hex(); // Dibuja el píxel solo si es más cercano
+                self.velocity[index] = velocity;
             }
         }
     }
@@ -63,6 +116,11 @@ impl Framebuffer {
         self.current_color = color;
     }
 
+    // Color actual desde el espacio lineal de los shaders: aquí se hace la única codificación a sRGB
+    pub fn set_current_linear_color(&mut self, color: LinearColor) {
+        self.current_color = color.to_srgb();
+    }
+
     // Obtener el color de un punto en las coordenadas especificadas
     pub fn get_point(&self, x: isize, y: isize) -> Option<u32> {
         if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
@@ -73,103 +131,161 @@ impl Framebuffer {
         }
     }
 
-    pub fn fill_polygon(&mut self, vertices: &Vec<[isize; 2]>, fill_color: Color) {
-        let min_y = vertices.iter().map(|v| v[1]).min().unwrap_or(0);
-        let max_y = vertices.iter().map(|v| v[1]).max().unwrap_or(0);
+    // Relleno suavizado de un contorno a profundidad 0; para huecos o cruces con otra regla, `fill_path`
+    pub fn fill_polygon(&mut self, vertices: &[Vec2], fill_color: Color) {
+        self.fill_path(&[vertices.to_vec()], FillRule::NonZero, fill_color, 1.0);
+    }
 
-        for y in min_y..=max_y {
-            let mut nodes = vec![];
-            let mut j = vertices.len() - 1;
-            for i in 0..vertices.len() {
-                let vi = vertices[i];
-                let vj = vertices[j];
-                if vi[1] < y && vj[1] >= y || vj[1] < y && vi[1] >= y {
-                    let intersect_x = vi[0] + (y - vi[1]) * (vj[0] - vi[0]) / (vj[1] - vi[1]);
-                    nodes.push(intersect_x);
-                }
-                j = i;
+    // Limita todo el dibujo (también la escena 3D de una vista) a un rectángulo; `None` lo desactiva
+    pub fn set_scissor(&mut self, scissor: Option<Rect>) {
+        self.scissor = scissor;
+    }
+
+    // Zona donde se puede dibujar: el recorte dentro de los bordes, o todo el framebuffer
+    pub fn clip_rect(&self) -> Rect {
+        let full = Rect { x: 0, y: 0, width: self.width, height: self.height };
+        self.scissor.map_or(full, |scissor| scissor.intersection(&full))
+    }
+
+    // Mezclar un color sobre el píxel según `alpha`. Usa `<=` para que el texto quede sobre el HUD a la misma profundidad.
+    // En una capa overlay no hay prueba de profundidad y el alfa se acumula en el byte alto.
+    pub fn blend_point(&mut self, x: isize, y: isize, z: f32, color: Color, alpha: f32) {
+        if self.scissor.is_some_and(|scissor| !scissor.contains(x, y)) {
+            return;
+        }
+
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height && alpha > 0.0 {
+            let index = (self.width * y as usize) + x as usize;
+            let alpha = alpha.min(1.0);
+
+            if self.overlay {
+                let pixel = self.buffer[index];
+                let destination_alpha = (pixel >> 24) as f32 / 255.0;
+                let (color, out_alpha) = BlendMode::Normal.composite(Color::from_hex(pixel), destination_alpha, color, alpha);
+                self.buffer[index] = (((out_alpha * 255.0).round() as u32) << 24) | color.to_hex();
+                return;
             }
-            nodes.sort();
-            for n in (0..nodes.len()).step_by(2) {
-                if n + 1 < nodes.len() {
-                    for x in nodes[n]..=nodes[n + 1] {
-                        self.set_current_color(fill_color);
-                        self.point(x, y, 0.0);
-                    }
-                }
+
+            if z <= self.zbuffer[index] {
+                self.zbuffer[index] = z;
+                let background = Color::from_hex(self.buffer[index]);
+                self.buffer[index] = background.lerp(&color, alpha).to_hex();
             }
         }
     }
 
-    pub fn draw_text(&mut self, x: usize, y: usize, text: &str, color: Color, scale: f32) {
-        // Cargar la fuente desde el archivo
-        let font_data = include_bytes!("../fonts/mai10.ttf");
-        let font = Font::try_from_bytes(font_data as &[u8]).unwrap();
-    
-        // Definir la escala (tamaño) del texto
-        let scale = Scale::uniform(scale); // Ajusta el tamaño según sea necesario
-    
-        // Establecer el color actual del framebuffer
-        self.set_current_color(color);
-    
-        // Obtener métricas verticales de la fuente
-        let v_metrics = font.v_metrics(scale);
-    
-        // Crear una posición de punto para el layout
-        let start_point = point(x as f32, y as f32 + v_metrics.ascent);
-    
-        // Generar los glifos posicionados para el texto dado
-        let glyphs: Vec<PositionedGlyph> = font.layout(text, scale, start_point).collect();
-    
-        // Dibujar cada glifo en el framebuffer
+    // Dibuja texto con `(x, y)` como esquina superior; `x` es el borde izquierdo, el centro o el borde derecho según `align`
+    pub fn draw_text(&mut self, x: f32, y: f32, text: &str, color: Color, scale: f32, align: TextAlign) {
+        self.draw_text_styled(x, y, text, &TextStyle::new(color), scale, align);
+    }
+
+    // Igual que `draw_text`, con sombra, contorno y degradado según `style`
+    pub fn draw_text_styled(&mut self, x: f32, y: f32, text: &str, style: &TextStyle, scale: f32, align: TextAlign) {
+        let glyphs = self.text.layout(text, scale, x, y, align);
+        self.draw_glyphs(&glyphs, style);
+    }
+
+    // Dibuja texto partido por palabras para que cada línea quepa en `max_width`
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_text_wrapped(&mut self, x: f32, y: f32, max_width: f32, text: &str, style: &TextStyle, scale: f32, align: TextAlign) {
+        let glyphs = self.text.layout_wrapped(text, scale, x, y, max_width, align);
+        self.draw_glyphs(&glyphs, style);
+    }
+
+    // Agrega una fuente de respaldo para caracteres que no estén en la fuente principal
+    pub fn load_fallback_font(&mut self, path: &str) -> Result<(), String> {
+        self.text.load_fallback(path)
+    }
+
+    // Ancho y alto en píxeles que ocupará el texto
+    pub fn measure_text(&self, text: &str, scale: f32) -> (f32, f32) {
+        self.text.measure(text, scale)
+    }
+
+    pub fn set_line_spacing(&mut self, line_spacing: f32) {
+        self.text.set_line_spacing(line_spacing);
+    }
+
+    // Dibuja sombra, contorno y relleno en ese orden, mezclando la cobertura de cada glifo para bordes suavizados
+    fn draw_glyphs(&mut self, glyphs: &[PlacedGlyph], style: &TextStyle) {
+        let outline = style.outline.map(|outline| (self.text.outlined(glyphs, outline.width), outline.color));
+
+        if let Some(shadow) = style.shadow {
+            // La sombra sigue la silueta exterior: la del contorno si lo hay
+            let silhouette = outline.as_ref().map_or(glyphs, |(outlined, _)| outlined.as_slice());
+            self.blend_glyphs(silhouette, shadow.offset_x, shadow.offset_y, shadow.opacity, |_| shadow.color);
+        }
+
+        if let Some((outlined, color)) = &outline {
+            self.blend_glyphs(outlined, 0, 0, 1.0, |_| *color);
+        }
+
+        self.blend_glyphs(glyphs, 0, 0, 1.0, |t| style.color_at(t));
+    }
+
+    // `color` recibe la altura relativa dentro de la línea (0 arriba, 1 abajo)
+    fn blend_glyphs<F: Fn(f32) -> Color>(&mut self, glyphs: &[PlacedGlyph], offset_x: i32, offset_y: i32, opacity: f32, color: F) {
         for glyph in glyphs {
-            if let Some(bounding_box) = glyph.pixel_bounding_box() {
-                glyph.draw(|gx, gy, gv| {
-                    let px = gx as i32 + bounding_box.min.x;
-                    let py = gy as i32 + bounding_box.min.y;
-    
-                    // Verificar que el valor del glifo sea suficientemente alto para dibujar
-                    if gv > 0.1 { // Ajusta el umbral según sea necesario
-                        self.point(px as isize, py as isize, 0.0);
-                    }
-                });
+            let bitmap = &glyph.bitmap;
+            for gy in 0..bitmap.height {
+                let y = glyph.y + gy as i32;
+                let row_color = color((y as f32 + 0.5 - glyph.line_top) / glyph.line_height);
+
+                for gx in 0..bitmap.width {
+                    let coverage = bitmap.coverage[gy * bitmap.width + gx] * opacity;
+                    self.blend_point((glyph.x + gx as i32 + offset_x) as isize, (y + offset_y) as isize, 0.0, row_color, coverage);
+                }
             }
         }
     }
 
-    pub fn draw_image(&mut self, image_path: &str, width: usize, height: usize) {
-        // Cargar la imagen
-        let img = image::open(image_path).expect("No se pudo cargar la imagen");
-        let img_width = img.width() as usize;
-        let img_height = img.height() as usize;
-    
-        // Calcular el factor de escala para ajustar la imagen al ancho del framebuffer
-        let scale_factor = width as f32 / img_width as f32;
-    
-        // Mantener la relación de aspecto y calcular la nueva altura
-        let new_height = (img_height as f32 * scale_factor) as usize;
-    
-        // Redimensionar la imagen manteniendo la relación de aspecto
-        let scaled_img = img.resize_exact(width as u32, new_height as u32, FilterType::Lanczos3);
-    
-        // Crear un buffer para el framebuffer
-        let mut buffer: Vec<u32> = vec![0; width * height];
-    
-        // Calcular el offset vertical para centrar la imagen en el framebuffer
-        let vertical_offset = (height.saturating_sub(new_height)) / 2;
-    
-        // Dibujar la imagen redimensionada en el framebuffer, centrada verticalmente
-        for y in 0..new_height.min(height) {
-            for x in 0..width {
-                let pixel = scaled_img.get_pixel(x as u32, y as u32);
-                let rgba = pixel.0;
-                let r = rgba[0] as u32;
-                let g = rgba[1] as u32;
-                let b = rgba[2] as u32;
-                let a = rgba[3] as u32;
-    
-                // Insertar el píxel en el buffer con el offset vertical
-                self.buffer[((y + vertical_offset) * width + x) as usize] = (a << 24) | (r << 16) | (g << 8) | b;
+    // Dibuja una textura transformada (escala, rotación alrededor del pivote, subrectángulo)
+    // mezclándola con alfa y tinte. Se dibuja sobre el HUD a profundidad 0, como el texto.
+    pub fn draw_sprite(&mut self, texture: &Texture, sprite: &Sprite) {
+        let source = sprite.source_rect(texture);
+        if source.width == 0 || source.height == 0 || sprite.scale_x == 0.0 || sprite.scale_y == 0.0 {
+            return;
+        }
+
+        let (sin, cos) = sprite.rotation.sin_cos();
+        let pivot_x = sprite.origin.0 * source.width as f32;
+        let pivot_y = sprite.origin.1 * source.height as f32;
+
+        // Esquinas del sprite en pantalla para acotar los píxeles a recorrer
+        let to_screen = |u: f32, v: f32| {
+            let lx = (u - pivot_x) * sprite.scale_x;
+            let ly = (v - pivot_y) * sprite.scale_y;
+            (sprite.x + lx * cos - ly * sin, sprite.y + lx * sin + ly * cos)
+        };
+        let corners = [
+            to_screen(0.0, 0.0),
+            to_screen(source.width as f32, 0.0),
+            to_screen(0.0, source.height as f32),
+            to_screen(source.width as f32, source.height as f32),
+        ];
+
+        // Texeles que cubre cada píxel de pantalla: más de uno cuando el sprite se reduce
+        let (footprint_u, footprint_v) = (1.0 / sprite.scale_x.abs(), 1.0 / sprite.scale_y.abs());
+
+        let min_x = corners.iter().map(|c| c.0).fold(f32::INFINITY, f32::min).floor().max(0.0) as usize;
+        let max_x = corners.iter().map(|c| c.0).fold(f32::NEG_INFINITY, f32::max).ceil().min(self.width as f32) as usize;
+        let min_y = corners.iter().map(|c| c.1).fold(f32::INFINITY, f32::min).floor().max(0.0) as usize;
+        let max_y = corners.iter().map(|c| c.1).fold(f32::NEG_INFINITY, f32::max).ceil().min(self.height as f32) as usize;
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                // Transformación inversa del centro del píxel al espacio de la textura
+                let dx = x as f32 + 0.5 - sprite.x;
+                let dy = y as f32 + 0.5 - sprite.y;
+                let u = (dx * cos + dy * sin) / sprite.scale_x + pivot_x;
+                let v = (-dx * sin + dy * cos) / sprite.scale_y + pivot_y;
+
+                if u < 0.0 || v < 0.0 || u >= source.width as f32 || v >= source.height as f32 {
+                    continue;
+                }
+
+                let (color, alpha) = sprite::sample_area(texture, &source, u, v, footprint_u, footprint_v, sprite.smooth);
+                self.blend_point(x as isize, y as isize, 0.0, color.blend_multiply(&sprite.tint), alpha * sprite.alpha);
             }
         }
     }
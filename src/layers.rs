@@ -0,0 +1,95 @@
+// layers.rs
+
+use crate::color::{BlendMode, Color};
+use crate::framebuffer::Framebuffer;
+
+// Capa de dibujo con su propia opacidad y visibilidad. Las capas overlay guardan alfa en el byte alto
+// de cada píxel; la capa de la escena 3D es opaca y no lo usa.
+pub struct Layer {
+    pub framebuffer: Framebuffer,
+    pub opacity: f32,
+    pub visible: bool,
+    pub blend: BlendMode, // Cómo se fusiona con las capas de abajo
+}
+
+impl Layer {
+    pub fn new(framebuffer: Framebuffer) -> Self {
+        Layer {
+            framebuffer,
+            opacity: 1.0,
+            visible: true,
+            blend: BlendMode::Normal,
+        }
+    }
+}
+
+// Mezcla las capas en orden (la primera queda al fondo) en un buffer listo para la ventana.
+// Las capas de otro tamaño (p. ej. la escena a menor resolución) se escalan con el vecino más cercano.
+pub struct Compositor {
+    pub width: usize,
+    pub height: usize,
+    pub buffer: Vec<u32>,
+}
+
+impl Compositor {
+    pub fn new(width: usize, height: usize) -> Self {
+        Compositor {
+            width,
+            height,
+            buffer: vec![0; width * height],
+        }
+    }
+
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        self.buffer = vec![0; width * height];
+    }
+
+    pub fn composite(&mut self, layers: &[&Layer]) {
+        self.buffer.fill(0);
+
+        for layer in layers.iter().filter(|layer| layer.visible && layer.opacity > 0.0) {
+            let framebuffer = &layer.framebuffer;
+            let opacity = (layer.opacity.min(1.0) * 255.0) as u32;
+
+            // Columna de origen de cada columna de salida
+            let columns: Vec<usize> = (0..self.width).map(|x| x * framebuffer.width / self.width).collect();
+
+            for y in 0..self.height {
+                let source = &framebuffer.buffer[(y * framebuffer.height / self.height) * framebuffer.width..];
+                let output = &mut self.buffer[y * self.width..(y + 1) * self.width];
+
+                for (output, &column) in output.iter_mut().zip(&columns) {
+                    let pixel = source[column];
+                    let alpha = if framebuffer.is_overlay() { (pixel >> 24) * opacity / 255 } else { opacity };
+                    if alpha == 0 {
+                        continue;
+                    }
+
+                    *output = match layer.blend {
+                        BlendMode::Normal => blend(*output, pixel, alpha),
+                        mode => {
+                            let (color, _) = mode.composite(Color::from_hex(*output), 1.0, Color::from_hex(pixel), alpha as f32 / 255.0);
+                            color.to_hex()
+                        }
+                    };
+                }
+            }
+        }
+    }
+}
+
+// `source` sobre `destination` con un alfa de 0 a 255, canal por canal
+fn blend(destination: u32, source: u32, alpha: u32) -> u32 {
+    if alpha >= 255 {
+        return source & 0x00FF_FFFF;
+    }
+
+    let channel = |shift: u32| {
+        let d = (destination >> shift) & 0xFF;
+        let s = (source >> shift) & 0xFF;
+        ((s * alpha + d * (255 - alpha) + 127) / 255) << shift
+    };
+    channel(16) | channel(8) | channel(0)
+}
@@ -1,8 +1,8 @@
 //main.rs
 
-use nalgebra_glm::{Vec3, Vec2};
-use minifb::{Key, Window, WindowOptions};
-use std::time::{Duration, Instant};
+use nalgebra_glm::{Mat4, Vec3, Vec2};
+use minifb::{Key, KeyRepeat, Window, WindowOptions};
+use std::time::Duration;
 use std::f32::consts::PI;
 
 mod framebuffer;
@@ -23,6 +23,25 @@ mod barrelRoll;
 mod colisionWarning;
 mod autopilot;
 mod audioPlayer;
+mod texture;
+mod bake;
+mod nebula;
+mod settings;
+mod catalog;
+mod text;
+mod rect;
+mod assets;
+mod sprite;
+mod shapes;
+mod layers;
+mod screenshot;
+mod recording;
+mod postprocess;
+mod flare;
+mod motion;
+mod ssao;
+mod render_target;
+mod viewport;
 
 use audioPlayer::AudioPlayer;
 use autopilot::Autopilot;
@@ -30,11 +49,29 @@ use colisionWarning::ColisionWarning;
 use barrelRoll::BarrelRoll;
 use minimap::Minimap;
 use skybox::Skybox;
+use nebula::ProceduralSky;
+use settings::{Settings, SkyboxKind};
+use bake::BakedSurface;
+use text::{TextAlign, TextStyle};
+use assets::Assets;
+use sprite::Sprite;
+use layers::{Compositor, Layer};
+use screenshot::downsample;
+use recording::Recorder;
+use postprocess::PostProcess;
+use flare::LensFlare;
+use motion::MotionBlur;
+use ssao::AmbientOcclusion;
+use render_target::RenderTarget;
+use viewport::{system_camera, ViewCamera, Viewport};
+use rect::Rect;
+use shapes::{Shapes, Stroke};
+use vertex::Vertex;
 use color::Color;
 use framebuffer::Framebuffer;
 use obj::Obj;
 use camera::Camera;
-use crate::render::{Uniforms, render, create_model_matrix, create_view_matrix, create_perspective_matrix, create_viewport_matrix};
+use crate::render::{Uniforms, render, create_model_matrix, FIELD_OF_VIEW};
 use fastnoise_lite::{FastNoiseLite, NoiseType, FractalType};
 
 fn create_noise(number: u8) -> FastNoiseLite {
@@ -111,22 +148,48 @@ fn create_ground_noise() -> FastNoiseLite {
 }
 
 fn main() {
-    let width = 900;
-    let height = 800;
+    let settings = Settings::load("settings.cfg");
+
+    // Tamaño actual de la ventana; cambia al redimensionarla
+    let mut width = 900;
+    let mut height = 800;
     let frame_delay = Duration::from_millis(16);
 
-    let mut framebuffer = Framebuffer::new(width, height);
+    // Escena 3D, HUD y menús se dibujan en capas separadas y se mezclan al final del cuadro.
+    // La escena se renderiza a `render_scale` del tamaño de la ventana y el compositor la amplía.
+    let (render_width, render_height) = render_size(width, height, settings.render_scale);
+    let mut scene = Layer::new(Framebuffer::new(render_width, render_height));
+    let mut hud = Layer::new(Framebuffer::new_overlay(width, height));
+    let mut menu = Layer::new(Framebuffer::new_overlay(width, height));
+    let mut compositor = Compositor::new(width, height);
+    let mut post_process = PostProcess::new(settings.post_process_chain());
+    let mut lens_flare = LensFlare::new(settings.lens_flare, settings.god_rays);
+    let mut motion_blur = MotionBlur::new(settings.motion_blur, settings.shutter_angle);
+    let mut ambient_occlusion = AmbientOcclusion::new(settings.ssao_radius, settings.ssao_intensity);
+    hud.opacity = settings.hud_opacity;
+    hud.blend = settings.hud_blend;
     let mut window = Window::new(
         "Rust Graphics - Renderer Example",
         width,
         height,
-        WindowOptions::default(),
+        WindowOptions {
+            resize: true,
+            ..WindowOptions::default()
+        },
     )
     .unwrap();
 
     window.update();
     
-    framebuffer.set_background_color(Color::new(0, 0, 0));
+    scene.framebuffer.set_background_color(Color::new(0, 0, 0));
+    menu.framebuffer.set_line_spacing(0.9);
+    if !settings.fallback_font.is_empty() {
+        for layer in [&mut hud, &mut menu] {
+            if let Err(err) = layer.framebuffer.load_fallback_font(&settings.fallback_font) {
+                eprintln!("Could not load fallback font {}", err);
+            }
+        }
+    }
     
     let mut translation = Vec3::new(-7000.0, 0.0, 0.0);
     let mut rotation_y = 0.0; // Cambiado a un único valor para la rotación Y
@@ -149,14 +212,37 @@ fn main() {
     let sphere = Obj::load("objs/sphere.obj").expect("Failed to load obj");
     let vertex_arrays_sphere = sphere.get_vertex_array();
 
-    let mut time = 0;
-    let mut is_alternate_render = false;
+    // Superficies de los planetas horneadas una vez y guardadas en caché en disco
+    let baked_surfaces: Vec<Option<BakedSurface>> = (0..=6)
+        .map(|number| bake::load_or_bake("cache/planets", number, &create_noise(number), 512, 256))
+        .collect();
+    let mut use_baked_surfaces = true;
 
-    let skybox = Skybox::new(10000);
+    let mut time = 0;
+    let mut layout = settings.layout;
+
+    let skybox = match settings.skybox {
+        SkyboxKind::Stars => Skybox::seeded(settings.sky_seed, &settings.star_layers),
+        // Fondo procedural (banda galáctica y nebulosas) horneado en un cube map al iniciar
+        SkyboxKind::Procedural => Skybox::procedural(&settings.star_layers, ProceduralSky::new(settings.sky_seed), Some(256)),
+        SkyboxKind::Image => Skybox::from_image(&settings.skybox_path).unwrap_or_else(|err| {
+            eprintln!("Could not load skybox {}: {}", settings.skybox_path, err);
+            Skybox::seeded(settings.sky_seed, &settings.star_layers)
+        }),
+        SkyboxKind::Catalog => match catalog::load_catalog(&settings.star_catalog, settings.limiting_magnitude) {
+            Ok(stars) => Skybox::from_stars(stars),
+            Err(err) => {
+                eprintln!("Could not load star catalogue: {}", err);
+                Skybox::seeded(settings.sky_seed, &settings.star_layers)
+            }
+        },
+    }
+    .with_twinkle(settings.twinkle);
 
-    let blink_interval = Duration::from_millis(1500);
-    let text_blink_interval = Duration::from_millis(200);
-    let mut last_blink_time = Instant::now();
+    // Parpadeos medidos en cuadros de `time`, no en tiempo real, para que al grabar ocurran en su momento
+    let blink_interval = frames(Duration::from_millis(1500), frame_delay);
+    let text_blink_interval = frames(Duration::from_millis(200), frame_delay);
+    let mut last_blink_time = time;
     let mut show_warning = false;
     let mut show_autopilot = false;
     let mut autopilot = Autopilot::new();
@@ -178,10 +264,22 @@ fn main() {
         (vertex_arrays_sphere.clone(), Vec3::new(2.0 * -16000.0, 0.0, 0.0), 1800.0, 6, 6.28),
     ];
 
+    // Vistas secundarias renderizadas fuera de pantalla y dibujadas en el HUD
+    let (mirror_rect, monitor_rect) = view_layout(&Rect { x: 0, y: 0, width, height });
+    let view_camera = || Camera {
+        eye: Vec3::zeros(),
+        center: Vec3::new(0.0, 0.0, -1.0),
+        up: Vec3::new(0.0, 1.0, 0.0),
+        has_changed: true,
+    };
+    let mut mirror = RenderTarget::new(mirror_rect.width, mirror_rect.height, view_camera());
+    let mut monitor = RenderTarget::new(monitor_rect.width, monitor_rect.height, view_camera());
+    let mut show_mirror = false;
+    let mut monitor_target: Option<usize> = None;
+
     let mut minimap = Minimap::new(
-        (width as isize - 100) / 4, 
-        height as isize / 4, 
-        Vec2::new((width - 120) as f32, 120.0), 
+        width,
+        height,
         Vec2::new(translation.x, translation.z),
         Vec2::new(celestial_bodies[1].1.x, celestial_bodies[1].1.z),
         Vec2::new(celestial_bodies[2].1.x, celestial_bodies[2].1.z),
@@ -194,73 +292,137 @@ fn main() {
     let mut warning_message = ColisionWarning::new("DANGER!".to_string(), "Inminent gravitational field".to_string(), Color::new(255,255,255), Color::new(255,0,0));
     let mut autopilot_message = ColisionWarning::new("Autopilot".to_string(), "Avoiding gravitational field".to_string(), Color::new(255,255,255), Color::new(254,138,24));
 
-    let begin_page = "src/ship_img.jpg";
+    let mut assets = Assets::new();
+    let begin_page = assets.image("src/ship_img.jpg").expect("No se pudo cargar la imagen");
     let mut show_text = true;
     let mut enter_pressed = false;
 
     begin_screen_ost.play();
 
     while window.is_open() && !enter_pressed && !window.is_key_down(minifb::Key::Escape) {
-        framebuffer.clear();
-        framebuffer.draw_image(&begin_page, width, height);
+        if let Some(size) = resized(&window, width, height) {
+            (width, height) = size;
+            resize_layers(width, height, settings.render_scale, &mut scene, &mut [&mut hud, &mut menu], &mut compositor);
+        }
+
+        // Ajustada al ancho de la escena y centrada verticalmente
+        let begin_page_scale = scene.framebuffer.width as f32 / begin_page.width as f32;
+        let begin_page_sprite = Sprite {
+            x: scene.framebuffer.width as f32 / 2.0,
+            y: scene.framebuffer.height as f32 / 2.0,
+            scale_x: begin_page_scale,
+            scale_y: begin_page_scale,
+            origin: (0.5, 0.5),
+            ..Sprite::default()
+        };
 
-        framebuffer.draw_text(width / 4, height - 750, "No UVG's Sky", Color::new(12,140,57), 100.0);
+        scene.framebuffer.clear();
+        scene.framebuffer.draw_sprite(&begin_page, &begin_page_sprite);
 
-        if last_blink_time.elapsed() >= text_blink_interval {
+        menu.framebuffer.clear();
+        menu.framebuffer.draw_text(width as f32 / 2.0, 50.0, "No UVG's Sky", Color::new(12,140,57), 100.0, TextAlign::Center);
+
+        if time - last_blink_time >= text_blink_interval {
             show_text = !show_text;
-            last_blink_time = Instant::now();
+            last_blink_time = time;
         }
         
         if show_text {
-            framebuffer.draw_text(width / 5, (4 * height) / 5 - 25, "Press ENTER to start game", Color::new(255,255,255), 70.0);
+            menu.framebuffer.draw_text_wrapped(width as f32 / 2.0, (4 * height) as f32 / 5.0 - 25.0, width as f32 - 80.0, "Press ENTER to start game", &TextStyle::new(Color::new(255,255,255)), 70.0, TextAlign::Center);
         }
 
         if window.is_key_down(minifb::Key::Enter) {
             enter_pressed = true;
+            menu.visible = false;
             begin_screen_ost.stop();
             gameplay_ost.play();
         }
 
-        window.update_with_buffer(&framebuffer.buffer, width, height).unwrap();
-        std::thread::sleep(Duration::from_millis(16));
+        compositor.composite(&[&scene, &menu]);
+        post_process.apply(&mut compositor.buffer, width, height);
+        window.update_with_buffer(&compositor.buffer, width, height).unwrap();
+        time += 1;
+        std::thread::sleep(frame_delay);
     }
 
+    let mut recorder: Option<Recorder> = None;
+
     while window.is_open() {
         if window.is_key_down(Key::Escape) {
             break;
         }
 
-        if window.is_key_down(Key::M) {
-            is_alternate_render = !is_alternate_render;
-            std::thread::sleep(Duration::from_millis(200));
-        } 
+        if let Some(size) = resized(&window, width, height) {
+            (width, height) = size;
+            resize_layers(width, height, settings.render_scale, &mut scene, &mut [&mut hud, &mut menu], &mut compositor);
+        }
 
-        time += 1;
+        if window.is_key_pressed(Key::M, KeyRepeat::No) {
+            layout = layout.next();
+        }
 
-        framebuffer.clear();
+        if window.is_key_pressed(Key::B, KeyRepeat::No) {
+            use_baked_surfaces = !use_baked_surfaces;
+        }
 
-        let noise = create_noise(0);
-        let model_matrix = create_model_matrix(translation, scale, Vec3::new(rotation_x, rotation_y, rotation_z));
-        let view_matrix = create_view_matrix(camera.eye, camera.center, camera.up);
-        let projection_matrix = create_perspective_matrix(width as f32, height as f32);
-        let viewport_matrix = create_viewport_matrix(width as f32, height as f32);
-        let uniforms_base = Uniforms { 
-            model_matrix, 
-            view_matrix,
-            projection_matrix,
-            viewport_matrix,
-            time,
-            noise,
-        };
+        if window.is_key_pressed(Key::H, KeyRepeat::No) {
+            hud.visible = !hud.visible;
+        }
 
-        skybox.render(&mut framebuffer, &uniforms_base, camera.eye);
+        if window.is_key_pressed(Key::V, KeyRepeat::No) {
+            motion_blur.enabled = !motion_blur.enabled;
+        }
 
-        
-        for (vertex_array, traslation, scale, number, _) in &celestial_bodies {
-             if is_in_center(*traslation, &mut camera, true) && !barrel_roll.active {
+        if window.is_key_pressed(Key::R, KeyRepeat::No) {
+            show_mirror = !show_mirror;
+        }
+
+        // Sin monitor, luego cada planeta en orden (el 0 es la estrella)
+        if window.is_key_pressed(Key::T, KeyRepeat::No) {
+            monitor_target = match monitor_target {
+                None => Some(1),
+                Some(index) if index + 1 < celestial_bodies.len() => Some(index + 1),
+                Some(_) => None,
+            };
+        }
+
+        if window.is_key_pressed(Key::F9, KeyRepeat::No) {
+            recorder = match recorder.take() {
+                Some(recording) => {
+                    finish_recording(recording);
+                    None
+                }
+                None => Recorder::start(&settings.record_dir, settings.record_format, width, height, frame_delay)
+                    .map_err(|err| eprintln!("Could not start recording {}", err))
+                    .ok(),
+            };
+        }
+
+        // A escala mayor que 1 la escena de este cuadro se renderiza en un framebuffer aparte más grande
+        let capture = window.is_key_pressed(Key::F12, KeyRepeat::No);
+        let capture_scale = if capture { settings.screenshot_scale } else { 1 };
+        let mut hires = (capture_scale > 1).then(|| {
+            Framebuffer::new(scene.framebuffer.width * capture_scale, scene.framebuffer.height * capture_scale)
+        });
+
+        time += 1;
+        motion_blur.begin_frame();
+
+        scene.framebuffer.clear();
+        hud.framebuffer.clear();
+        let target = hires.as_mut().unwrap_or(&mut scene.framebuffer);
+
+        let star_position = celestial_bodies[0].1;
+        let viewports = layout.viewports(width, height);
+        let has_chase_view = viewports.iter().any(|viewport| viewport.camera == ViewCamera::Chase);
+
+        // Las advertencias dependen de la cámara de persecución aunque la distribución actual no la muestre
+        let (mut draw_warning, mut draw_autopilot) = (false, false);
+        for (_, traslation, _, _, _) in &celestial_bodies {
+             if is_in_center(*traslation, &camera, true) && !barrel_roll.active {
 
                 let distance = (camera.eye - *traslation).magnitude();
-                if distance < 3500.0 && last_blink_time.elapsed() >= blink_interval && !show_autopilot  {
+                if distance < 3500.0 && time - last_blink_time >= blink_interval && !show_autopilot  {
                     show_autopilot = false;
                     show_warning = !show_warning;
                 }
@@ -270,23 +432,14 @@ fn main() {
                     show_autopilot = true;
                 }
                 if show_warning {
-                    warning_message.render(&mut framebuffer);
+                    draw_warning = true;
                 }
                 if show_autopilot {
-                    autopilot_message.render(&mut framebuffer);
+                    draw_autopilot = true;
                     if !autopilot.active {
                         autopilot.start();
                     }
                 }
-
-                let noise = create_noise(*number);
-                let model_matrix = create_model_matrix(*traslation, *scale, Vec3::zeros());
-                let uniforms = Uniforms {
-                    model_matrix,
-                    noise,
-                    ..uniforms_base
-                };
-                render(&mut framebuffer, &uniforms, vertex_array, *number);
             }
         }
 
@@ -327,55 +480,267 @@ fn main() {
             }
         }
 
-        if !is_alternate_render {
+        // La nave solo se controla con la cámara de persecución en pantalla
+        if has_chase_view {
 
             // Manejo de entrada actualizado con desacoplamiento
             if window.is_key_down(Key::W) && !autopilot.active {
                 handle_input(
-                    &window, 
-                    &mut translation, 
-                    &mut rotation_y, 
+                    &window,
+                    &mut translation,
+                    &mut rotation_y,
                     &mut rotation_x,
                     &mut rotation_z,
-                    &mut camera, 
+                    &mut camera,
                     &mut minimap,
                     &mut barrel_roll,
                 );
             }
-            
+
             if autopilot.active {
                 handle_autopilot(
-                    &mut translation, 
-                    &mut rotation_y, 
+                    &mut translation,
+                    &mut rotation_y,
                     &mut rotation_x,
-                    &mut camera, 
+                    &mut camera,
                     &mut minimap,
                     autopilot.simulated_keys.clone(),
                 );
             }
-            
+
 
             handle_camera(
                 &window,
                 &mut camera,
             );
+        }
+
+        let model_matrix = create_model_matrix(translation, scale, Vec3::new(rotation_x, rotation_y, rotation_z));
+        let overview_camera = system_camera();
+        // Ids para el desenfoque de movimiento: la nave (0) y cada cuerpo (su número), por cámara
+        let objects_per_camera = celestial_bodies.len() + 1;
+
+        for viewport in &viewports {
+            // Las vistas se definen en píxeles de la ventana y la escena puede renderizarse a otra escala
+            let rect = viewport.rect.scaled(target.width as f32 / width as f32, target.height as f32 / height as f32);
+            let view = Viewport { rect, ..*viewport };
+            let view_camera = match viewport.camera {
+                ViewCamera::Chase => &camera,
+                ViewCamera::System => &overview_camera,
+            };
+            let object = |id: usize| viewport.camera as usize * objects_per_camera + id;
+            let (view_matrix, projection_matrix, viewport_matrix) = view.matrices(view_camera);
+            target.set_scissor(Some(rect));
+
+            let uniforms_base = Uniforms {
+                model_matrix,
+                view_matrix,
+                projection_matrix,
+                viewport_matrix,
+                time,
+                noise: create_noise(0),
+                light_direction: direction_to_light(translation, star_position),
+                camera_position: view_camera.eye,
+                surface: None,
+                previous_matrix: motion_blur.previous_matrix(object(0), projection_matrix * view_matrix * model_matrix),
+                ambient_occlusion: None,
+            };
+
+            skybox.render(target, view_camera, view.field_of_view, time);
+
+            for (vertex_array, traslation, scale, number, _) in &celestial_bodies {
+                // La vista del sistema abarca todas las órbitas; la de persecución dibuja lo que tiene delante
+                let visible = match viewport.camera {
+                    ViewCamera::Chase => is_in_center(*traslation, view_camera, true) && !barrel_roll.active,
+                    ViewCamera::System => true,
+                };
+                if !visible {
+                    continue;
+                }
+
+                let noise = create_noise(*number);
+                let model_matrix = create_model_matrix(*traslation, *scale, Vec3::zeros());
+                let surface = if use_baked_surfaces {
+                    baked_surfaces[*number as usize].as_ref()
+                } else {
+                    None
+                };
+                let uniforms = Uniforms {
+                    model_matrix,
+                    noise,
+                    light_direction: direction_to_light(*traslation, star_position),
+                    surface,
+                    previous_matrix: motion_blur.previous_matrix(object(*number as usize), projection_matrix * view_matrix * model_matrix),
+                    ..uniforms_base
+                };
+                render(target, &uniforms, vertex_array, *number);
+            }
+
+            // La oclusión ambiental solo se nota de cerca, con la cámara de persecución
+            let use_ssao = settings.ssao && viewport.camera == ViewCamera::Chase;
+            if use_ssao {
+                ambient_occlusion.render(&uniforms_base, &vertex_arrays, target.width, target.height, &rect);
+            }
+            let ship_uniforms = Uniforms {
+                noise: create_noise(0),
+                ambient_occlusion: use_ssao.then_some(&ambient_occlusion),
+                ..uniforms_base
+            };
+            render(target, &ship_uniforms, &vertex_arrays, 0);
+
+            // La esfera del modelo mide 0.5 de radio, así que la estrella mide la mitad de su escala
+            motion_blur.apply(target);
+            lens_flare.render(target, &uniforms_base, star_position, celestial_bodies[0].2 * 0.5);
+
+            // El HUD se dibuja a tamaño de ventana, dentro del rectángulo de la vista
+            if viewport.hud.warnings && draw_warning {
+                warning_message.render(&mut hud.framebuffer, &viewport.rect);
+            }
+            if viewport.hud.warnings && draw_autopilot {
+                autopilot_message.render(&mut hud.framebuffer, &viewport.rect);
+            }
+
+            if viewport.hud.minimap {
+                minimap.set_layout(&viewport.rect);
+                minimap.render(&mut hud.framebuffer);
+            }
+
+            let scene_view = SceneView {
+                skybox: &skybox,
+                bodies: &celestial_bodies,
+                surfaces: &baked_surfaces,
+                use_baked_surfaces,
+                ship: Some((&vertex_arrays, model_matrix, translation)),
+                time,
+            };
+            let (mirror_rect, monitor_rect) = view_layout(&viewport.rect);
+
+            // Retrovisor: mira hacia atrás desde encima de la nave, reflejado como un espejo
+            if viewport.hud.mirror && show_mirror {
+                let (_, _, forward) = camera.basis();
+                mirror.camera.eye = translation + camera.up * 15.0;
+                mirror.camera.center = mirror.camera.eye - forward;
+                mirror.camera.up = camera.up;
+                mirror.resize(mirror_rect.width, mirror_rect.height);
+                render_view(&mut mirror, &SceneView { ship: None, ..scene_view });
+
+                let sprite = Sprite {
+                    x: (mirror_rect.x + mirror_rect.width as isize) as f32,
+                    y: mirror_rect.y as f32,
+                    scale_x: -1.0,
+                    ..Sprite::default()
+                };
+                hud.framebuffer.draw_sprite(mirror.texture(), &sprite);
+                hud.framebuffer.stroke_rounded_rect(&mirror_rect, 6.0, &Stroke { color: Color::new(3,252,53), width: 2.0, ..Stroke::default() });
+            }
 
-            render(&mut framebuffer, &uniforms_base, &vertex_arrays, 0);
+            // Monitor del objetivo: el planeta elegido visto desde el lado de la nave
+            let monitor_body = monitor_target.filter(|_| viewport.hud.monitor).and_then(|index| celestial_bodies.get(index));
+            if let Some((_, position, scale, _, _)) = monitor_body {
+                let toward_ship = (translation - *position).try_normalize(1e-6).unwrap_or(Vec3::z());
+                monitor.camera.eye = *position + toward_ship * *scale * 1.5;
+                monitor.camera.center = *position;
+                monitor.resize(monitor_rect.width, monitor_rect.height);
+                render_view(&mut monitor, &scene_view);
+
+                monitor.blit(&mut hud.framebuffer, monitor_rect.x, monitor_rect.y);
+                hud.framebuffer.stroke_rounded_rect(&monitor_rect, 6.0, &Stroke { color: Color::new(3,252,53), width: 2.0, ..Stroke::default() });
+            }
 
-            minimap.render(&mut framebuffer);
-        } else {
-            camera.eye = Vec3::new(-0.00038838302, 88555.33, 8885.168);
+            // Borde de las vistas que no ocupan toda la ventana
+            if viewport.rect != (Rect { x: 0, y: 0, width, height }) {
+                hud.framebuffer.stroke_rounded_rect(&viewport.rect, 0.0, &Stroke { color: Color::new(3,252,53), width: 2.0, ..Stroke::default() });
+            }
         }
+        target.set_scissor(None);
 
+        if let Some(hires) = &hires {
+            downsample(hires, &mut scene.framebuffer, capture_scale);
+        }
+        if capture {
+            save_capture(&settings, &scene, &hud, hires, &mut post_process);
+        }
+
+        compositor.composite(&[&scene, &hud, &menu]);
+        post_process.apply(&mut compositor.buffer, width, height);
+        if let Some(Err(err)) = recorder.as_mut().map(|recording| recording.write_frame(&compositor.buffer)) {
+            eprintln!("Could not record frame {}", err);
+            recorder = None;
+        }
         window
-            .update_with_buffer(&framebuffer.buffer, width, height)
+            .update_with_buffer(&compositor.buffer, width, height)
             .unwrap();
 
-        std::thread::sleep(frame_delay);
+        // La simulación avanza un paso por cuadro. Al grabar no se espera: cada cuadro del video
+        // dura `frame_delay` aunque tarde más en renderizarse, así que se reproduce a velocidad real.
+        if recorder.is_none() {
+            std::thread::sleep(frame_delay);
+        }
+    }
+
+    if let Some(recording) = recorder {
+        finish_recording(recording);
     }
 }
 
-fn is_in_center(translation: Vec3, camera: &mut Camera, is_up: bool) -> bool {
+// Compone la escena (y el HUD si así se configura) y la guarda en el directorio de capturas.
+// Con `hires` la captura tiene su tamaño; si no, el de la ventana (el del HUD).
+fn save_capture(settings: &Settings, scene: &Layer, hud: &Layer, hires: Option<Framebuffer>, post_process: &mut PostProcess) {
+    let (width, height) = match &hires {
+        Some(framebuffer) => (framebuffer.width, framebuffer.height),
+        None => (hud.framebuffer.width, hud.framebuffer.height),
+    };
+    let hires_scene = hires.map(Layer::new);
+    let scene = hires_scene.as_ref().unwrap_or(scene);
+
+    // El HUD no depende de la cámara, así que el compositor basta para ampliarlo
+    let mut layers = vec![scene];
+    if settings.screenshot_hud {
+        layers.push(hud);
+    }
+
+    let mut compositor = Compositor::new(width, height);
+    compositor.composite(&layers);
+    post_process.apply(&mut compositor.buffer, width, height);
+
+    if let Err(err) = screenshot::save_screenshot(&settings.screenshot_dir, settings.screenshot_format, &compositor.buffer, width, height) {
+        eprintln!("Could not save screenshot {}", err);
+    }
+}
+
+// Tamaño de la escena para una ventana de `width`×`height` a la escala de render indicada
+fn render_size(width: usize, height: usize, render_scale: f32) -> (usize, usize) {
+    let scaled = |size: usize| ((size as f32 * render_scale).round() as usize).max(1);
+    (scaled(width), scaled(height))
+}
+
+// Nuevo tamaño de la ventana si cambió (minimizada mide 0 y se ignora)
+fn resized(window: &Window, width: usize, height: usize) -> Option<(usize, usize)> {
+    let size = window.get_size();
+    (size != (width, height) && size.0 > 0 && size.1 > 0).then_some(size)
+}
+
+fn resize_layers(width: usize, height: usize, render_scale: f32, scene: &mut Layer, overlays: &mut [&mut Layer], compositor: &mut Compositor) {
+    let (render_width, render_height) = render_size(width, height, render_scale);
+    scene.framebuffer.resize(render_width, render_height);
+    for layer in overlays.iter_mut() {
+        layer.framebuffer.resize(width, height);
+    }
+    compositor.resize(width, height);
+}
+
+// Cuántos cuadros de `frame_delay` dura `interval`
+fn frames(interval: Duration, frame_delay: Duration) -> u32 {
+    (interval.as_millis() / frame_delay.as_millis()) as u32
+}
+
+fn finish_recording(recorder: Recorder) {
+    if let Err(err) = recorder.finish() {
+        eprintln!("Could not finish recording {}", err);
+    }
+}
+
+fn is_in_center(translation: Vec3, camera: &Camera, is_up: bool) -> bool {
 
     if is_up {
         let camera_xz = Vec2::new(camera.eye.x, camera.eye.z);
@@ -584,6 +949,80 @@ fn handle_camera(window: &Window, camera: &mut Camera) {
     }
 }
 
+type CelestialBody = (Vec<Vertex>, Vec3, f32, u8, f32);
+
+// Lo que se dibuja en una vista secundaria: fondo, cuerpos celestes y, si se indica, la nave
+// (vértices, matriz de modelo y posición)
+#[derive(Clone, Copy)]
+struct SceneView<'a> {
+    skybox: &'a Skybox,
+    bodies: &'a [CelestialBody],
+    surfaces: &'a [Option<BakedSurface>],
+    use_baked_surfaces: bool,
+    ship: Option<(&'a [Vertex], Mat4, Vec3)>,
+    time: u32,
+}
+
+// Renderiza la escena desde la cámara del target, sin la lógica de juego del cuadro principal
+fn render_view(target: &mut RenderTarget, scene: &SceneView) {
+    let (view_matrix, projection_matrix, viewport_matrix) = target.matrices();
+    let camera = &target.camera;
+    let framebuffer = &mut target.framebuffer;
+    let (_, _, forward) = camera.basis();
+    let star_position = scene.bodies[0].1;
+
+    framebuffer.clear();
+    scene.skybox.render(framebuffer, camera, FIELD_OF_VIEW, scene.time);
+
+    let uniforms_for = |model_matrix: Mat4, number: u8, position: Vec3| Uniforms {
+        model_matrix,
+        view_matrix,
+        projection_matrix,
+        viewport_matrix,
+        time: scene.time,
+        noise: create_noise(number),
+        light_direction: direction_to_light(position, star_position),
+        camera_position: camera.eye,
+        surface: None,
+        previous_matrix: projection_matrix * view_matrix * model_matrix,
+        ambient_occlusion: None,
+    };
+
+    for (vertex_array, position, scale, number, _) in scene.bodies {
+        // Lo que queda detrás de la cámara no se dibuja
+        if (*position - camera.eye).dot(&forward) < -*scale {
+            continue;
+        }
+
+        let surface = if scene.use_baked_surfaces { scene.surfaces[*number as usize].as_ref() } else { None };
+        let uniforms = Uniforms {
+            surface,
+            ..uniforms_for(create_model_matrix(*position, *scale, Vec3::zeros()), *number, *position)
+        };
+        render(framebuffer, &uniforms, vertex_array, *number);
+    }
+
+    if let Some((vertex_array, model_matrix, position)) = scene.ship {
+        render(framebuffer, &uniforms_for(model_matrix, 0, position), vertex_array, 0);
+    }
+}
+
+// Rectángulos en pantalla del retrovisor (arriba al centro) y del monitor del objetivo (abajo a la izquierda) de la vista `area`
+fn view_layout(area: &Rect) -> (Rect, Rect) {
+    let margin = 20;
+    let (width, height) = (area.width, area.height);
+    let mirror_width = width * 3 / 10;
+    let mirror = Rect { x: area.x + ((width - mirror_width) / 2) as isize, y: area.y + margin, width: mirror_width, height: mirror_width / 3 };
+
+    let side = width.min(height) / 4;
+    let monitor = Rect { x: area.x + margin, y: area.y + height as isize - margin - side as isize, width: side, height: side };
+    (mirror, monitor)
+}
+
+fn direction_to_light(position: Vec3, light_position: Vec3) -> Vec3 {
+    (light_position - position).try_normalize(1e-6).unwrap_or_else(Vec3::zeros)
+}
+
 fn lerp(a: f32, b: f32, t: f32) -> f32 {
     a + (b - a) * t
 }
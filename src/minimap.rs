@@ -2,7 +2,14 @@
 use nalgebra_glm::Vec2;
 use crate::framebuffer::Framebuffer;
 use crate::color::Color;
-use crate::polygon::Polygon;
+use crate::rect::Rect;
+use crate::polygon::{FillRule, Polygon};
+
+// Unidades del mundo por píxel de un minimapa de `REFERENCE_SIZE` píxeles
+const MAP_SCALE: f32 = 500.0;
+const REFERENCE_SIZE: f32 = 200.0;
+// Separación entre el minimapa y la esquina superior derecha de su vista
+const MARGIN: isize = 20;
 
 pub struct Minimap {
     height: isize,
@@ -14,13 +21,14 @@ pub struct Minimap {
     planet3_pos: Vec2,
     planet4_pos: Vec2,
     planet5_pos: Vec2,
+    zoom: f32, // Escala del mapa respecto a `REFERENCE_SIZE`
 }
 
 impl Minimap {
+    // El minimapa se ancla a la esquina superior derecha de una pantalla de `screen_width`×`screen_height`
     pub fn new(
-        height: isize, 
-        width: isize, 
-        position: Vec2, 
+        screen_width: usize,
+        screen_height: usize,
         ship_pos: Vec2, 
         planet1_pos: Vec2,
         planet2_pos: Vec2,
@@ -28,135 +36,89 @@ impl Minimap {
         planet4_pos: Vec2,
         planet5_pos: Vec2,
     ) -> Self {
-        Minimap {
-            height,
-            width,
-            position,
+        let mut minimap = Minimap {
+            height: 0,
+            width: 0,
+            position: Vec2::zeros(),
             ship_pos,
             planet1_pos,
             planet2_pos,
             planet3_pos,
             planet4_pos,
-            planet5_pos
-        }
+            planet5_pos,
+            zoom: 1.0,
+        };
+        minimap.set_layout(&Rect { x: 0, y: 0, width: screen_width, height: screen_height });
+        minimap
+    }
+
+    // Recalcula tamaño y posición para la vista `area` de la pantalla: un cuarto del lado menor, a `MARGIN` de su esquina
+    pub fn set_layout(&mut self, area: &Rect) {
+        let side = area.width.min(area.height) as isize / 4;
+        self.width = side;
+        self.height = side;
+        self.position = Vec2::new(
+            (area.x + area.width as isize - MARGIN - side / 2) as f32,
+            (area.y + MARGIN + side / 2) as f32,
+        );
+        self.zoom = side as f32 / REFERENCE_SIZE;
     }
 
     pub fn render(&mut self, framebuffer: &mut Framebuffer) {
-        let mut vertex = Vec::new();
-        let mut vertex_back = Vec::new();
-
-        vertex.push([(self.position.x) as isize - self.width / 2, (self.position.y) as isize - self.height / 2]); //First vertex
-        vertex.push([(self.position.x) as isize + (self.width / 2), (self.position.y) as isize - self.height / 2]); //Second vertex
-        vertex.push([(self.position.x) as isize + (self.width / 2), (self.position.y) as isize + self.height / 2]); //Third vertex
-        vertex.push([(self.position.x) as isize - (self.width / 2), (self.position.y) as isize + self.height / 2]); //Fourth vertex
-        
-        vertex_back.push([(self.position.x) as isize - self.width / 2 - 2, (self.position.y) as isize - self.height / 2 - 2]); //First vertex
-        vertex_back.push([(self.position.x) as isize + (self.width / 2) + 2, (self.position.y) as isize - self.height / 2 - 2]); //Second vertex
-        vertex_back.push([(self.position.x) as isize + (self.width / 2) + 2, (self.position.y) as isize + self.height / 2 + 2]); //Third vertex
-        vertex_back.push([(self.position.x) as isize - (self.width / 2) - 2, (self.position.y) as isize + self.height / 2 + 2]); //Fourth vertex
+        let (half_width, half_height) = ((self.width / 2) as f32, (self.height / 2) as f32);
+        let corners = |margin: f32| vec![
+            self.position + Vec2::new(-half_width - margin, -half_height - margin),
+            self.position + Vec2::new(half_width + margin, -half_height - margin),
+            self.position + Vec2::new(half_width + margin, half_height + margin),
+            self.position + Vec2::new(-half_width - margin, half_height + margin),
+        ];
+        let vertex = corners(0.0);
+        let vertex_back = corners(2.0);
 
         let border_color = Color::new(3,252,53);
         let inner_color = Color::new(0,0,0);
 
-        framebuffer.set_current_color(Color::new(255,0,0));
-
-        //Transform ship translation to minimap coords
-        let mut coord_x = self.position.x as isize + (self.ship_pos.x / 500.0) as isize;
-        let mut coord_y = self.position.y as isize + (self.ship_pos.y / 500.0) as isize;
+        // El HUD se dibuja en orden: el marco es el contorno exterior con el interior como hueco,
+        // luego el fondo del mapa y encima las marcas
+        framebuffer.fill_path(&[vertex_back, vertex.clone()], FillRule::EvenOdd, border_color, 1.0);
+        framebuffer.polygon(&vertex, border_color, inner_color);
 
-        framebuffer.point(coord_x, coord_y, 0.0);
+        //Ship coords
+        framebuffer.set_current_color(Color::new(255,0,0));
+        let ship = self.to_map(self.ship_pos);
+        framebuffer.point(ship.x as isize, ship.y as isize, 0.0);
 
-        //Star coordinates
+        //Star coords
         framebuffer.set_current_color(Color::new(255,255,255));
+        Minimap::dot(framebuffer, self.position);
+
+        //Planet coords
+        let planets = [
+            (self.planet1_pos, Color::new(79,22,19)),
+            (self.planet2_pos, Color::new(109,218,222)),
+            (self.planet3_pos, Color::new(87,179,82)),
+            (self.planet4_pos, Color::new(125,19,14)),
+            (self.planet5_pos, Color::new(2,5,64)),
+        ];
+        for (planet_pos, color) in planets {
+            framebuffer.set_current_color(color);
+            Minimap::dot(framebuffer, self.to_map(planet_pos));
+        }
+    }
 
-        framebuffer.point((self.position.x - 1.0) as isize, (self.position.y - 1.0) as isize, 0.0);
-        framebuffer.point((self.position.x - 1.0) as isize, self.position.y as isize, 0.0);
-        framebuffer.point((self.position.x - 1.0) as isize, (self.position.y + 1.0) as isize, 0.0);
-        framebuffer.point(self.position.x as isize, (self.position.y - 1.0) as isize, 0.0);
-        framebuffer.point(self.position.x as isize, self.position.y as isize, 0.0);
-        framebuffer.point(self.position.x as isize, (self.position.y + 1.0) as isize, 0.0);
-        framebuffer.point((self.position.x + 1.0) as isize, (self.position.y - 1.0) as isize, 0.0);
-        framebuffer.point((self.position.x + 1.0) as isize, self.position.y as isize, 0.0);
-        framebuffer.point((self.position.x + 1.0) as isize, (self.position.y + 1.0) as isize, 0.0);
-
-        //Planet 1 coords
-        framebuffer.set_current_color(Color::new(79,22,19));
-        coord_x = self.position.x as isize + (self.planet1_pos.x / 500.0) as isize;
-        coord_y = self.position.y as isize + (self.planet1_pos.y / 500.0) as isize;
-
-        framebuffer.point((coord_x - 1) as isize, (coord_y - 1) as isize, 0.0);
-        framebuffer.point((coord_x - 1) as isize as isize, coord_y as isize, 0.0);
-        framebuffer.point((coord_x - 1) as isize as isize, (coord_y + 1) as isize, 0.0);
-        framebuffer.point(coord_x as isize, (coord_y - 1) as isize, 0.0);
-        framebuffer.point(coord_x as isize, coord_y as isize, 0.0);
-        framebuffer.point(coord_x as isize, (coord_y + 1) as isize, 0.0);
-        framebuffer.point((coord_x + 1) as isize, (coord_y - 1) as isize, 0.0);
-        framebuffer.point((coord_x + 1) as isize, coord_y as isize, 0.0);
-        framebuffer.point((coord_x + 1) as isize, (coord_y + 1) as isize, 0.0);
-        
-        //Planet 2 coords
-        framebuffer.set_current_color(Color::new(109,218,222));
-        coord_x = self.position.x as isize + (self.planet2_pos.x / 500.0) as isize;
-        coord_y = self.position.y as isize + (self.planet2_pos.y / 500.0) as isize;
-
-        framebuffer.point((coord_x - 1) as isize, (coord_y - 1) as isize, 0.0);
-        framebuffer.point((coord_x - 1) as isize as isize, coord_y as isize, 0.0);
-        framebuffer.point((coord_x - 1) as isize as isize, (coord_y + 1) as isize, 0.0);
-        framebuffer.point(coord_x as isize, (coord_y - 1) as isize, 0.0);
-        framebuffer.point(coord_x as isize, coord_y as isize, 0.0);
-        framebuffer.point(coord_x as isize, (coord_y + 1) as isize, 0.0);
-        framebuffer.point((coord_x + 1) as isize, (coord_y - 1) as isize, 0.0);
-        framebuffer.point((coord_x + 1) as isize, coord_y as isize, 0.0);
-        framebuffer.point((coord_x + 1) as isize, (coord_y + 1) as isize, 0.0);
-        
-        
-        //Planet 3 coords
-        framebuffer.set_current_color(Color::new(87,179,82));
-        coord_x = self.position.x as isize + (self.planet3_pos.x / 500.0) as isize;
-        coord_y = self.position.y as isize + (self.planet3_pos.y / 500.0) as isize;
-
-        framebuffer.point((coord_x - 1) as isize, (coord_y - 1) as isize, 0.0);
-        framebuffer.point((coord_x - 1) as isize as isize, coord_y as isize, 0.0);
-        framebuffer.point((coord_x - 1) as isize as isize, (coord_y + 1) as isize, 0.0);
-        framebuffer.point(coord_x as isize, (coord_y - 1) as isize, 0.0);
-        framebuffer.point(coord_x as isize, coord_y as isize, 0.0);
-        framebuffer.point(coord_x as isize, (coord_y + 1) as isize, 0.0);
-        framebuffer.point((coord_x + 1) as isize, (coord_y - 1) as isize, 0.0);
-        framebuffer.point((coord_x + 1) as isize, coord_y as isize, 0.0);
-        framebuffer.point((coord_x + 1) as isize, (coord_y + 1) as isize, 0.0);
-        
-        //Planet 4 coords
-        framebuffer.set_current_color(Color::new(125,19,14));
-        coord_x = self.position.x as isize + (self.planet4_pos.x / 500.0) as isize;
-        coord_y = self.position.y as isize + (self.planet4_pos.y / 500.0) as isize;
-
-        framebuffer.point((coord_x - 1) as isize, (coord_y - 1) as isize, 0.0);
-        framebuffer.point((coord_x - 1) as isize as isize, coord_y as isize, 0.0);
-        framebuffer.point((coord_x - 1) as isize as isize, (coord_y + 1) as isize, 0.0);
-        framebuffer.point(coord_x as isize, (coord_y - 1) as isize, 0.0);
-        framebuffer.point(coord_x as isize, coord_y as isize, 0.0);
-        framebuffer.point(coord_x as isize, (coord_y + 1) as isize, 0.0);
-        framebuffer.point((coord_x + 1) as isize, (coord_y - 1) as isize, 0.0);
-        framebuffer.point((coord_x + 1) as isize, coord_y as isize, 0.0);
-        framebuffer.point((coord_x + 1) as isize, (coord_y + 1) as isize, 0.0);
-        
-        //Planet 5 coords
-        framebuffer.set_current_color(Color::new(2,5,64));
-        coord_x = self.position.x as isize + (self.planet5_pos.x / 500.0) as isize;
-        coord_y = self.position.y as isize + (self.planet5_pos.y / 500.0) as isize;
-
-        framebuffer.point((coord_x - 1) as isize, (coord_y - 1) as isize, 0.0);
-        framebuffer.point((coord_x - 1) as isize as isize, coord_y as isize, 0.0);
-        framebuffer.point((coord_x - 1) as isize as isize, (coord_y + 1) as isize, 0.0);
-        framebuffer.point(coord_x as isize, (coord_y - 1) as isize, 0.0);
-        framebuffer.point(coord_x as isize, coord_y as isize, 0.0);
-        framebuffer.point(coord_x as isize, (coord_y + 1) as isize, 0.0);
-        framebuffer.point((coord_x + 1) as isize, (coord_y - 1) as isize, 0.0);
-        framebuffer.point((coord_x + 1) as isize, coord_y as isize, 0.0);
-        framebuffer.point((coord_x + 1) as isize, (coord_y + 1) as isize, 0.0);
+    // Marca de 3×3 píxeles centrada en `center`
+    fn dot(framebuffer: &mut Framebuffer, center: Vec2) {
+        let (x, y) = (center.x as isize, center.y as isize);
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                framebuffer.point(x + dx, y + dy, 0.0);
+            }
+        }
+    }
 
-        framebuffer.polygon(&vertex, border_color, inner_color);
-        framebuffer.polygon(&vertex_back, border_color, border_color);
+    // Posición del mundo (x, z) a coordenadas de pantalla del minimapa
+    fn to_map(&self, world: Vec2) -> Vec2 {
+        self.position + world * self.zoom / MAP_SCALE
     }
 
     pub fn update_ship_pos(&mut self, pos_x : f32, pos_y : f32) {
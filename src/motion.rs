@@ -0,0 +1,196 @@
+// motion.rs
+
+use crate::color::{channels, pack};
+use crate::framebuffer::Framebuffer;
+use nalgebra_glm::{Mat4, Vec2};
+
+// Lado de las baldosas con que se busca la velocidad máxima de la zona
+const TILE: usize = 16;
+const SAMPLES: usize = 12;
+
+// Desenfoque de movimiento por objeto a partir del buffer de velocidad del framebuffer.
+// Recuerda la matriz de cada objeto del cuadro anterior para que el rasterizador calcule la velocidad.
+pub struct MotionBlur {
+    pub enabled: bool,
+    shutter_angle: f32, // 360° = todo el recorrido del cuadro, 180° = la mitad
+    frame: u64,
+    previous: Vec<Option<(u64, Mat4, Mat4)>>, // Por objeto: último cuadro en que se dibujó, su matriz y la del cuadro anterior
+    scratch: Vec<u32>,
+    tiles: Vec<Vec2>,
+}
+
+impl MotionBlur {
+    pub fn new(enabled: bool, shutter_angle: f32) -> Self {
+        MotionBlur {
+            enabled,
+            shutter_angle,
+            frame: 0,
+            previous: Vec::new(),
+            scratch: Vec::new(),
+            tiles: Vec::new(),
+        }
+    }
+
+    pub fn begin_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    // Matriz (proyección × vista × modelo) con que se dibujó `object` en el cuadro anterior, y guarda la actual.
+    // Un objeto que no se dibujó en el cuadro anterior se toma como quieto; si se registra dos veces en el mismo
+    // cuadro, la segunda recibe lo mismo que la primera.
+    pub fn previous_matrix(&mut self, object: usize, matrix: Mat4) -> Mat4 {
+        if self.previous.len() <= object {
+            self.previous.resize(object + 1, None);
+        }
+
+        let previous = match self.previous[object] {
+            Some((frame, _, previous)) if frame == self.frame => return previous,
+            Some((frame, last, _)) if frame + 1 == self.frame => last,
+            _ => matrix,
+        };
+        self.previous[object] = Some((self.frame, matrix, previous));
+        previous
+    }
+
+    // Desenfoca solo dentro del recorte del framebuffer, así el rastro no pasa de una vista a otra
+    pub fn apply(&mut self, framebuffer: &mut Framebuffer) {
+        if !self.enabled || self.shutter_angle <= 0.0 {
+            return;
+        }
+
+        let area = framebuffer.clip_rect();
+        let (width, height) = (area.width, area.height);
+        let (left, top, stride) = (area.x as usize, area.y as usize, framebuffer.width);
+        let index_of = |x: usize, y: usize| (top + y) * stride + left + x;
+        let scale = self.shutter_angle / 360.0;
+        // Límite del rastro, para que los saltos de cámara no emborronen toda la pantalla
+        let max_length = 0.05 * height as f32;
+        let velocity_at = |index: usize| {
+            let velocity = framebuffer.velocity[index] * scale;
+            let length = velocity.magnitude();
+            if length > max_length { velocity * (max_length / length) } else { velocity }
+        };
+
+        // Velocidad más larga de cada baldosa y luego de sus vecinas, así los bordes de un objeto
+        // en movimiento se extienden sobre el fondo quieto
+        let (tiles_x, tiles_y) = (width.div_ceil(TILE), height.div_ceil(TILE));
+        let mut tile_max = vec![Vec2::zeros(); tiles_x * tiles_y];
+        for y in 0..height {
+            for x in 0..width {
+                let velocity = velocity_at(index_of(x, y));
+                let tile = &mut tile_max[(y / TILE) * tiles_x + x / TILE];
+                if velocity.magnitude_squared() > tile.magnitude_squared() {
+                    *tile = velocity;
+                }
+            }
+        }
+
+        self.tiles.clear();
+        for ty in 0..tiles_y {
+            for tx in 0..tiles_x {
+                let mut longest = Vec2::zeros();
+                for ny in ty.saturating_sub(1)..(ty + 2).min(tiles_y) {
+                    for nx in tx.saturating_sub(1)..(tx + 2).min(tiles_x) {
+                        let velocity = tile_max[ny * tiles_x + nx];
+                        if velocity.magnitude_squared() > longest.magnitude_squared() {
+                            longest = velocity;
+                        }
+                    }
+                }
+                self.tiles.push(longest);
+            }
+        }
+
+        self.scratch.clear();
+        self.scratch.extend_from_slice(&framebuffer.buffer);
+
+        for y in 0..height {
+            for x in 0..width {
+                let direction = self.tiles[(y / TILE) * tiles_x + x / TILE];
+                if direction.magnitude_squared() < 0.25 {
+                    continue;
+                }
+
+                let index = index_of(x, y);
+                let center_speed = velocity_at(index).magnitude();
+                let mut sum = channels(self.scratch[index]);
+                let mut total = 1.0;
+                // Desplazamiento distinto por píxel para que las muestras no formen bandas
+                let jitter = (52.982_918 * (0.067_110_56 * x as f32 + 0.005_837_15 * y as f32).fract()).fract();
+
+                for i in 0..SAMPLES {
+                    let t = (i as f32 + jitter) / SAMPLES as f32 - 0.5;
+                    let sx = (x as f32 + direction.x * t).round();
+                    let sy = (y as f32 + direction.y * t).round();
+                    if sx < 0.0 || sy < 0.0 || sx as usize >= width || sy as usize >= height {
+                        continue;
+                    }
+
+                    // Una muestra cuenta si su movimiento alcanza este píxel o si el de este píxel la alcanza
+                    let sample = index_of(sx as usize, sy as usize);
+                    let distance = direction.magnitude() * t.abs();
+                    if velocity_at(sample).magnitude() >= distance || center_speed >= distance {
+                        let color = channels(self.scratch[sample]);
+                        for channel in 0..3 {
+                            sum[channel] += color[channel];
+                        }
+                        total += 1.0;
+                    }
+                }
+
+                framebuffer.buffer[index] = pack(sum.map(|channel| channel / total));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translation(x: f32) -> Mat4 {
+        nalgebra_glm::translation(&nalgebra_glm::vec3(x, 0.0, 0.0))
+    }
+
+    #[test]
+    fn first_frame_is_treated_as_still() {
+        let mut blur = MotionBlur::new(true, 180.0);
+        blur.begin_frame();
+        assert_eq!(blur.previous_matrix(3, translation(1.0)), translation(1.0));
+    }
+
+    #[test]
+    fn consecutive_frames_return_the_last_matrix() {
+        let mut blur = MotionBlur::new(true, 180.0);
+        blur.begin_frame();
+        blur.previous_matrix(0, translation(1.0));
+        blur.begin_frame();
+        assert_eq!(blur.previous_matrix(0, translation(2.0)), translation(1.0));
+        blur.begin_frame();
+        assert_eq!(blur.previous_matrix(0, translation(3.0)), translation(2.0));
+    }
+
+    #[test]
+    fn skipped_frame_resets_the_motion() {
+        let mut blur = MotionBlur::new(true, 180.0);
+        blur.begin_frame();
+        blur.previous_matrix(0, translation(1.0));
+        blur.begin_frame(); // El objeto no se dibuja en este cuadro
+        blur.begin_frame();
+        assert_eq!(blur.previous_matrix(0, translation(5.0)), translation(5.0));
+    }
+
+    #[test]
+    fn same_frame_registration_repeats_the_previous_matrix() {
+        let mut blur = MotionBlur::new(true, 180.0);
+        blur.begin_frame();
+        blur.previous_matrix(0, translation(1.0));
+        blur.begin_frame();
+        assert_eq!(blur.previous_matrix(0, translation(2.0)), translation(1.0));
+        assert_eq!(blur.previous_matrix(0, translation(2.0)), translation(1.0));
+
+        // El cuadro siguiente parte de la primera matriz registrada
+        blur.begin_frame();
+        assert_eq!(blur.previous_matrix(0, translation(3.0)), translation(2.0));
+    }
+}
@@ -0,0 +1,97 @@
+// nebula.rs
+
+use nalgebra_glm::Vec3;
+use fastnoise_lite::{FastNoiseLite, NoiseType, FractalType};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use crate::color::Color;
+use crate::shader::smoothstep;
+
+// Fondo procedural: banda galáctica con franjas de polvo y nubes de nebulosa.
+// Todo se deriva de `seed`, así que la misma semilla produce el mismo cielo.
+pub struct ProceduralSky {
+    pub seed: u64,
+    galactic_pole: Vec3,
+    galactic_core: Vec3,
+    band_width: f32,
+    glow_noise: FastNoiseLite,
+    dust_noise: FastNoiseLite,
+    cloud_noise: FastNoiseLite,
+    tint_noise: FastNoiseLite,
+}
+
+impl ProceduralSky {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        // Orientación de la galaxia: un polo aleatorio y el núcleo sobre el plano de la banda
+        let galactic_pole = random_direction(&mut rng);
+        let helper = if galactic_pole.y.abs() < 0.9 { Vec3::y() } else { Vec3::x() };
+        let galactic_core = galactic_pole.cross(&helper).normalize();
+
+        let noise_seed = rng.gen::<i32>();
+
+        ProceduralSky {
+            seed,
+            galactic_pole,
+            galactic_core,
+            band_width: 0.18,
+            glow_noise: fbm_noise(noise_seed, NoiseType::OpenSimplex2, FractalType::FBm, 0.02, 4),
+            dust_noise: fbm_noise(noise_seed.wrapping_add(1), NoiseType::Perlin, FractalType::Ridged, 0.03, 5),
+            cloud_noise: fbm_noise(noise_seed.wrapping_add(2), NoiseType::OpenSimplex2, FractalType::FBm, 0.012, 5),
+            tint_noise: fbm_noise(noise_seed.wrapping_add(3), NoiseType::OpenSimplex2, FractalType::None, 0.008, 1),
+        }
+    }
+
+    // Intensidad de la banda galáctica en [0, 1] según la latitud galáctica
+    fn band(&self, direction: &Vec3) -> f32 {
+        let latitude = direction.dot(&self.galactic_pole);
+        (-(latitude / self.band_width).powi(2)).exp()
+    }
+
+    // Densidad relativa de estrellas: más alta dentro de la banda
+    pub fn star_density(&self, direction: &Vec3) -> f32 {
+        0.2 + 0.8 * self.band(&direction.normalize())
+    }
+
+    pub fn sample(&self, direction: &Vec3) -> Color {
+        let d = direction.normalize();
+        let p = d * 100.0;
+
+        let band = self.band(&d);
+        let core = (-(1.0 - d.dot(&self.galactic_core)) * 4.0).exp();
+
+        // Brillo difuso de la banda con franjas de polvo oscuras hacia el centro
+        let glow = self.glow_noise.get_noise_3d(p.x, p.y, p.z) * 0.5 + 0.5;
+        let dust = self.dust_noise.get_noise_3d(p.x, p.y, p.z) * 0.5 + 0.5;
+        let lanes = smoothstep(0.55, 0.8, dust) * band.powi(3);
+        let band_intensity = band * (0.5 + 0.5 * glow) * (1.0 - 0.85 * lanes) * (0.6 + 0.8 * core);
+
+        let band_color = Color::new(120, 110, 95).lerp(&Color::new(200, 170, 130), core);
+
+        // Nubes de nebulosa de colores
+        let clouds = self.cloud_noise.get_noise_3d(p.x, p.y, p.z) * 0.5 + 0.5;
+        let nebula = smoothstep(0.55, 0.9, clouds);
+        let tint = self.tint_noise.get_noise_3d(p.x, p.y, p.z) * 0.5 + 0.5;
+        let nebula_color = Color::new(170, 40, 120).lerp(&Color::new(30, 120, 170), tint);
+
+        band_color * (band_intensity * 0.7) + nebula_color * (nebula * 0.4)
+    }
+}
+
+fn fbm_noise(seed: i32, noise_type: NoiseType, fractal_type: FractalType, frequency: f32, octaves: i32) -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(seed);
+    noise.set_noise_type(Some(noise_type));
+    noise.set_fractal_type(Some(fractal_type));
+    noise.set_frequency(Some(frequency));
+    noise.set_fractal_octaves(Some(octaves));
+    noise
+}
+
+// Dirección uniforme en la esfera
+pub fn random_direction<R: Rng>(rng: &mut R) -> Vec3 {
+    let z: f32 = rng.gen_range(-1.0..1.0);
+    let theta: f32 = rng.gen_range(0.0..std::f32::consts::TAU);
+    let r = (1.0 - z * z).sqrt();
+    Vec3::new(r * theta.cos(), r * theta.sin(), z)
+}
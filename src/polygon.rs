@@ -4,13 +4,15 @@ use crate::framebuffer::Framebuffer;
 use crate::line::Line;
 use crate::color::Color;
 use nalgebra_glm as glm;
+use nalgebra_glm::Vec2;
 
 pub trait Polygon {
-    fn polygon(&mut self, arr: &Vec<[isize; 2]>, border_color: Color, fill_color: Color);
+    fn polygon(&mut self, arr: &[Vec2], border_color: Color, fill_color: Color);
+    fn fill_path(&mut self, contours: &[Vec<Vec2>], rule: FillRule, color: Color, alpha: f32);
 }
 
 impl Polygon for Framebuffer {
-    fn polygon(&mut self, arr: &Vec<[isize; 2]>, border_color: Color, fill_color: Color) {
+    fn polygon(&mut self, arr: &[Vec2], border_color: Color, fill_color: Color) {
         if arr.len() < 2 {
             return; // No se puede formar un polígono con menos de 2 puntos
         }
@@ -29,10 +31,217 @@ impl Polygon for Framebuffer {
             };
 
             // Convertimos los valores a f64 y creamos glm::TVec3<f64>
-            let vertex1 = glm::vec3(first_pos[0] as f64, first_pos[1] as f64, 0.0);
-            let vertex2 = glm::vec3(last_pos[0] as f64, last_pos[1] as f64, 0.0);
+            let vertex1 = glm::vec3(first_pos.x as f64, first_pos.y as f64, 0.0);
+            let vertex2 = glm::vec3(last_pos.x as f64, last_pos.y as f64, 0.0);
 
             self.line(vertex1, vertex2);
         }
     }
+
+    // Relleno suavizado de uno o varios contornos (cóncavos, que se cruzan o con huecos) con mezcla alfa
+    fn fill_path(&mut self, contours: &[Vec<Vec2>], rule: FillRule, color: Color, alpha: f32) {
+        let (width, height) = (self.width, self.height);
+        scan_coverage(contours, rule, SUBSAMPLES, width, height, |x, y, coverage| {
+            self.blend_point(x as isize, y as isize, 0.0, color, coverage * alpha);
+        });
+    }
+}
+
+// Regla para decidir qué está dentro cuando los contornos se cruzan o se anidan
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FillRule {
+    EvenOdd, // Dentro si se cruza un número impar de bordes (los contornos anidados son huecos)
+    NonZero, // Dentro si la suma de direcciones de los bordes no es cero
+}
+
+// Sub-líneas de barrido por fila de píxeles para el suavizado
+const SUBSAMPLES: usize = 4;
+
+// Rasteriza uno o varios contornos cerrados con vértices flotantes. Para cada píxel cubierto llama
+// a `emit(x, y, cobertura)`. Con `samples = 1` se muestrea sólo el centro de cada fila; horizontalmente
+// la cobertura siempre es exacta.
+pub fn scan_coverage<F: FnMut(usize, usize, f32)>(
+    contours: &[Vec<Vec2>],
+    rule: FillRule,
+    samples: usize,
+    width: usize,
+    height: usize,
+    mut emit: F,
+) {
+    // Bordes no horizontales como (arriba, abajo, dirección)
+    let mut edges = Vec::new();
+    for contour in contours {
+        for i in 0..contour.len() {
+            let a = contour[i];
+            let b = contour[(i + 1) % contour.len()];
+            if a.y != b.y {
+                edges.push(if a.y < b.y { (a, b, 1) } else { (b, a, -1) });
+            }
+        }
+    }
+
+    if edges.is_empty() || width == 0 {
+        return;
+    }
+
+    let min_y = edges.iter().map(|e| e.0.y).fold(f32::INFINITY, f32::min).floor().max(0.0) as usize;
+    let max_y = edges.iter().map(|e| e.1.y).fold(f32::NEG_INFINITY, f32::max).ceil().min(height as f32).max(0.0) as usize;
+
+    let weight = 1.0 / samples as f32;
+    let mut row = vec![0.0f32; width];
+    let mut crossings: Vec<(f32, i32)> = Vec::new();
+
+    for y in min_y..max_y {
+        let (mut first, mut last) = (width, 0);
+
+        for s in 0..samples {
+            let sample_y = y as f32 + (s as f32 + 0.5) / samples as f32;
+
+            crossings.clear();
+            for (top, bottom, direction) in &edges {
+                if sample_y >= top.y && sample_y < bottom.y {
+                    let t = (sample_y - top.y) / (bottom.y - top.y);
+                    crossings.push((top.x + t * (bottom.x - top.x), *direction));
+                }
+            }
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            // Se recorren los cruces de izquierda a derecha acumulando el winding
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].1;
+                let inside = match rule {
+                    FillRule::EvenOdd => winding % 2 != 0,
+                    FillRule::NonZero => winding != 0,
+                };
+
+                let start = pair[0].0.clamp(0.0, width as f32);
+                let end = pair[1].0.clamp(0.0, width as f32);
+                if !inside || end <= start {
+                    continue;
+                }
+
+                let (start_pixel, end_pixel) = (start as usize, (end as usize).min(width - 1));
+                first = first.min(start_pixel);
+                last = last.max(end_pixel);
+
+                if start_pixel == end_pixel {
+                    row[start_pixel] += (end - start) * weight;
+                } else {
+                    row[start_pixel] += (start_pixel as f32 + 1.0 - start) * weight;
+                    for value in &mut row[start_pixel + 1..end_pixel] {
+                        *value += weight;
+                    }
+                    row[end_pixel] += (end - end_pixel as f32) * weight;
+                }
+            }
+        }
+
+        if first > last {
+            continue;
+        }
+
+        for (x, value) in row.iter_mut().enumerate().take(last + 1).skip(first) {
+            if *value > 0.0 {
+                emit(x, y, value.min(1.0));
+            }
+            *value = 0.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: usize = 20;
+
+    // Cobertura de cada píxel de una cuadrícula de SIZE×SIZE
+    fn coverage(contours: &[Vec<Vec2>], rule: FillRule) -> Vec<f32> {
+        let mut grid = vec![0.0; SIZE * SIZE];
+        scan_coverage(contours, rule, SUBSAMPLES, SIZE, SIZE, |x, y, value| grid[y * SIZE + x] = value);
+        grid
+    }
+
+    fn at(grid: &[f32], x: usize, y: usize) -> f32 {
+        grid[y * SIZE + x]
+    }
+
+    fn rectangle(x0: f32, y0: f32, x1: f32, y1: f32) -> Vec<Vec2> {
+        vec![Vec2::new(x0, y0), Vec2::new(x1, y0), Vec2::new(x1, y1), Vec2::new(x0, y1)]
+    }
+
+    #[test]
+    fn fills_a_concave_shape_without_its_notch() {
+        // Una L: el cuadrante superior derecho queda fuera
+        let shape = vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(8.0, 0.0),
+            Vec2::new(8.0, 4.0),
+            Vec2::new(4.0, 4.0),
+            Vec2::new(4.0, 8.0),
+            Vec2::new(0.0, 8.0),
+        ];
+        let grid = coverage(&[shape], FillRule::NonZero);
+
+        assert_eq!(at(&grid, 1, 1), 1.0);
+        assert_eq!(at(&grid, 6, 2), 1.0);
+        assert_eq!(at(&grid, 2, 6), 1.0);
+        assert_eq!(at(&grid, 6, 6), 0.0);
+        assert_eq!(grid.iter().sum::<f32>(), 48.0);
+    }
+
+    #[test]
+    fn star_center_depends_on_the_fill_rule() {
+        // Pentagrama: cada vértice salta uno, así que el pentágono central tiene winding 2
+        let star: Vec<Vec2> = (0..5)
+            .map(|i| {
+                let angle = (i * 2) as f32 * std::f32::consts::TAU / 5.0 - std::f32::consts::FRAC_PI_2;
+                Vec2::new(10.0 + 9.0 * angle.cos(), 10.0 + 9.0 * angle.sin())
+            })
+            .collect();
+
+        let even_odd = coverage(std::slice::from_ref(&star), FillRule::EvenOdd);
+        let non_zero = coverage(&[star], FillRule::NonZero);
+
+        assert_eq!(at(&even_odd, 10, 10), 0.0);
+        assert_eq!(at(&non_zero, 10, 10), 1.0);
+        // Las puntas tienen winding 1 y se rellenan igual con las dos reglas
+        assert!(at(&even_odd, 10, 4) > 0.9);
+        assert_eq!(at(&even_odd, 10, 4), at(&non_zero, 10, 4));
+    }
+
+    #[test]
+    fn inner_contour_cuts_a_hole() {
+        let outer = rectangle(2.0, 2.0, 12.0, 12.0);
+        let inner = rectangle(5.0, 5.0, 9.0, 9.0);
+        let mut reversed = inner.clone();
+        reversed.reverse();
+
+        // Con even-odd cualquier contorno anidado es un hueco
+        let even_odd = coverage(&[outer.clone(), inner.clone()], FillRule::EvenOdd);
+        assert_eq!(at(&even_odd, 3, 3), 1.0);
+        assert_eq!(at(&even_odd, 7, 7), 0.0);
+
+        // Con nonzero sólo si va en sentido contrario al exterior
+        let same_direction = coverage(&[outer.clone(), inner], FillRule::NonZero);
+        assert_eq!(at(&same_direction, 7, 7), 1.0);
+        let opposite_direction = coverage(&[outer, reversed], FillRule::NonZero);
+        assert_eq!(at(&opposite_direction, 3, 3), 1.0);
+        assert_eq!(at(&opposite_direction, 7, 7), 0.0);
+    }
+
+    #[test]
+    fn edges_inside_a_pixel_give_partial_coverage() {
+        let grid = coverage(&[rectangle(0.5, 0.5, 3.25, 3.0)], FillRule::NonZero);
+
+        // Horizontalmente la cobertura es exacta
+        assert_eq!(at(&grid, 1, 1), 1.0);
+        assert_eq!(at(&grid, 0, 1), 0.5);
+        assert_eq!(at(&grid, 3, 1), 0.25);
+        // Verticalmente se cuentan las sub-líneas dentro: 2 de 4 en la primera fila
+        assert_eq!(at(&grid, 1, 0), 0.5);
+        assert_eq!(at(&grid, 0, 0), 0.25);
+        assert_eq!(at(&grid, 1, 3), 0.0);
+    }
 }
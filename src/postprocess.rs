@@ -0,0 +1,198 @@
+// postprocess.rs
+
+use crate::color::{channels, pack, Color};
+
+const BAYER_4X4: [[f32; 4]; 4] = [
+    [0.0, 8.0, 2.0, 10.0],
+    [12.0, 4.0, 14.0, 6.0],
+    [3.0, 11.0, 1.0, 9.0],
+    [15.0, 7.0, 13.0, 5.0],
+];
+
+// Paleta a la que se reduce la imagen al difuminar
+#[derive(Debug, Clone)]
+pub enum Palette {
+    Levels(u32),       // Niveles por canal (2 = 8 colores)
+    Colors(Vec<Color>), // Colores fijos; se usa el más cercano
+}
+
+// Efectos de pantalla completa. Las distancias se miden en fracciones de la pantalla
+// para que se vean igual a cualquier resolución.
+#[derive(Debug, Clone)]
+pub enum Effect {
+    Vignette { strength: f32, radius: f32 },
+    ChromaticAberration { offset: f32 }, // Separación de rojo y azul en el borde
+    FilmGrain { amount: f32 },
+    Crt { curvature: f32, scanlines: f32 },
+    Dither { palette: Palette },
+}
+
+// Cadena de efectos que se aplica en orden al buffer final antes de mostrarlo
+pub struct PostProcess {
+    effects: Vec<Effect>,
+    scratch: Vec<u32>,
+    frame: u32, // Cambia el grano en cada cuadro
+}
+
+impl PostProcess {
+    pub fn new(effects: Vec<Effect>) -> Self {
+        PostProcess {
+            effects,
+            scratch: Vec::new(),
+            frame: 0,
+        }
+    }
+
+    // `buffer` en formato 0x00RRGGBB, como el de la ventana
+    pub fn apply(&mut self, buffer: &mut [u32], width: usize, height: usize) {
+        self.frame = self.frame.wrapping_add(1);
+
+        for effect in &self.effects {
+            match effect {
+                Effect::Vignette { strength, radius } => vignette(buffer, width, height, *strength, *radius),
+                Effect::ChromaticAberration { offset } => {
+                    self.scratch.clear();
+                    self.scratch.extend_from_slice(buffer);
+                    chromatic_aberration(&self.scratch, buffer, width, height, *offset);
+                }
+                Effect::FilmGrain { amount } => film_grain(buffer, width, height, *amount, self.frame),
+                Effect::Crt { curvature, scanlines } => {
+                    self.scratch.clear();
+                    self.scratch.extend_from_slice(buffer);
+                    crt(&self.scratch, buffer, width, height, *curvature, *scanlines);
+                }
+                Effect::Dither { palette } => dither(buffer, width, height, palette),
+            }
+        }
+    }
+}
+
+// Coordenadas del centro del píxel relativas al centro de la pantalla, en [-1, 1]
+fn centered(x: usize, y: usize, width: usize, height: usize) -> (f32, f32) {
+    (
+        (x as f32 + 0.5) / width as f32 * 2.0 - 1.0,
+        (y as f32 + 0.5) / height as f32 * 2.0 - 1.0,
+    )
+}
+
+// Oscurece hacia las esquinas a partir de `radius` (0 = centro, 1 = esquina)
+fn vignette(buffer: &mut [u32], width: usize, height: usize, strength: f32, radius: f32) {
+    for y in 0..height {
+        for x in 0..width {
+            let (u, v) = centered(x, y, width, height);
+            let distance = (u * u + v * v).sqrt() / std::f32::consts::SQRT_2;
+            let t = ((distance - radius) / (1.0 - radius).max(1e-3)).clamp(0.0, 1.0);
+            let factor = 1.0 - strength * t * t * (3.0 - 2.0 * t);
+
+            let index = y * width + x;
+            buffer[index] = pack(channels(buffer[index]).map(|channel| channel * factor));
+        }
+    }
+}
+
+// Rojo desplazado hacia afuera y azul hacia adentro, más separados cuanto más lejos del centro
+fn chromatic_aberration(source: &[u32], buffer: &mut [u32], width: usize, height: usize, offset: f32) {
+    let sample = |x: f32, y: f32| {
+        let x = (x.round().max(0.0) as usize).min(width - 1);
+        let y = (y.round().max(0.0) as usize).min(height - 1);
+        channels(source[y * width + x])
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            let (u, v) = centered(x, y, width, height);
+            let (dx, dy) = (u * offset * width as f32 / 2.0, v * offset * height as f32 / 2.0);
+
+            let (fx, fy) = (x as f32, y as f32);
+            let red = sample(fx + dx, fy + dy)[0];
+            let [_, green, _] = channels(source[y * width + x]);
+            let blue = sample(fx - dx, fy - dy)[2];
+            buffer[y * width + x] = pack([red, green, blue]);
+        }
+    }
+}
+
+// Ruido de luminancia distinto en cada cuadro
+fn film_grain(buffer: &mut [u32], width: usize, height: usize, amount: f32, frame: u32) {
+    for y in 0..height {
+        for x in 0..width {
+            let noise = hash(x as u32, y as u32, frame) * 2.0 - 1.0;
+            let index = y * width + x;
+            buffer[index] = pack(channels(buffer[index]).map(|channel| channel + noise * amount * 255.0));
+        }
+    }
+}
+
+// Pantalla curva (distorsión de barril, negro fuera de la imagen) con líneas de barrido
+fn crt(source: &[u32], buffer: &mut [u32], width: usize, height: usize, curvature: f32, scanlines: f32) {
+    // Una línea oscura cada dos filas a 800 píxeles de alto, proporcional a otras resoluciones
+    let period = ((height as f32 / 400.0).round() as usize).max(2);
+
+    for y in 0..height {
+        let darken = if (y / (period / 2)) % 2 == 1 { 1.0 - scanlines } else { 1.0 };
+
+        for x in 0..width {
+            let (u, v) = centered(x, y, width, height);
+            let bend = 1.0 + curvature * (u * u + v * v);
+            let (u, v) = (u * bend, v * bend);
+
+            let index = y * width + x;
+            if u.abs() > 1.0 || v.abs() > 1.0 {
+                buffer[index] = 0;
+                continue;
+            }
+
+            let sx = (((u + 1.0) / 2.0 * width as f32) as usize).min(width - 1);
+            let sy = (((v + 1.0) / 2.0 * height as f32) as usize).min(height - 1);
+            buffer[index] = pack(channels(source[sy * width + sx]).map(|channel| channel * darken));
+        }
+    }
+}
+
+// Difuminado ordenado (Bayer 4×4) hacia la paleta
+fn dither(buffer: &mut [u32], width: usize, height: usize, palette: &Palette) {
+    let colors: Vec<[f32; 3]> = match palette {
+        Palette::Colors(colors) => colors.iter().map(|color| channels(color.to_hex())).collect(),
+        Palette::Levels(_) => Vec::new(),
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            let threshold = (BAYER_4X4[y % 4][x % 4] + 0.5) / 16.0 - 0.5;
+            let index = y * width + x;
+            let pixel = channels(buffer[index]);
+
+            buffer[index] = match palette {
+                Palette::Levels(levels) => {
+                    let steps = ((*levels).max(2) - 1) as f32;
+                    pack(pixel.map(|channel| {
+                        let level = (channel / 255.0 * steps + threshold).round().clamp(0.0, steps);
+                        level / steps * 255.0
+                    }))
+                }
+                Palette::Colors(_) => {
+                    // El umbral se escala a la separación aproximada entre colores de la paleta
+                    let spread = 255.0 / colors.len().max(1) as f32;
+                    let target = pixel.map(|channel| channel + threshold * spread);
+                    let nearest = colors.iter().min_by(|a, b| {
+                        distance(a, &target).total_cmp(&distance(b, &target))
+                    });
+                    nearest.map_or(buffer[index], |color| pack(*color))
+                }
+            };
+        }
+    }
+}
+
+fn distance(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+}
+
+// Valor pseudoaleatorio en [0, 1) a partir de la posición y el cuadro
+fn hash(x: u32, y: u32, frame: u32) -> f32 {
+    let mut h = x.wrapping_mul(0x8DA6_B343) ^ y.wrapping_mul(0xD816_3841) ^ frame.wrapping_mul(0xCB1A_B31F);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5BD1_E995);
+    h ^= h >> 15;
+    (h >> 8) as f32 / (1u32 << 24) as f32
+}
@@ -0,0 +1,177 @@
+// recording.rs
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+use crate::screenshot::{save_image, timestamp, ImageFormat};
+
+// Salida de una grabación: secuencia de imágenes numeradas o un único flujo Y4M sin comprimir
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecordFormat {
+    Y4m,
+    Images(ImageFormat),
+}
+
+enum Output {
+    Y4m(BufWriter<File>),
+    Images { dir: String, format: ImageFormat },
+}
+
+// Graba cada cuadro compuesto. Los cuadros se tratan como pasos fijos de `frame_delay`,
+// así el video se reproduce a velocidad real aunque el render vaya más lento.
+pub struct Recorder {
+    output: Output,
+    width: usize,
+    height: usize,
+    frames: usize,
+    path: String,
+}
+
+impl Recorder {
+    pub fn start(dir: &str, format: RecordFormat, width: usize, height: usize, frame_delay: Duration) -> Result<Self, String> {
+        fs::create_dir_all(dir).map_err(|err| format!("{}: {}", dir, err))?;
+        let name = format!("recording-{}", timestamp());
+
+        let (output, path) = match format {
+            RecordFormat::Y4m => {
+                let path = Path::new(dir).join(format!("{}.y4m", name)).to_string_lossy().into_owned();
+                let file = File::create(&path).map_err(|err| format!("{}: {}", path, err))?;
+                let mut writer = BufWriter::new(file);
+
+                // Cuadros por segundo como fracción exacta: 1_000_000 / microsegundos por cuadro
+                writeln!(writer, "YUV4MPEG2 W{} H{} F1000000:{} Ip A1:1 C420jpeg", width, height, frame_delay.as_micros())
+                    .map_err(|err| format!("{}: {}", path, err))?;
+                (Output::Y4m(writer), path)
+            }
+            RecordFormat::Images(format) => {
+                let path = Path::new(dir).join(name).to_string_lossy().into_owned();
+                fs::create_dir_all(&path).map_err(|err| format!("{}: {}", path, err))?;
+                (Output::Images { dir: path.clone(), format }, path)
+            }
+        };
+
+        Ok(Recorder { output, width, height, frames: 0, path })
+    }
+
+    // `buffer` es el buffer de la ventana (0x00RRGGBB) del tamaño indicado en `start`
+    pub fn write_frame(&mut self, buffer: &[u32]) -> Result<(), String> {
+        if buffer.len() != self.width * self.height {
+            return Err(format!("{}: frame size changed while recording", self.path));
+        }
+
+        match &mut self.output {
+            Output::Y4m(writer) => {
+                let frame = yuv420(buffer, self.width, self.height);
+                writer
+                    .write_all(b"FRAME\n")
+                    .and_then(|_| writer.write_all(&frame))
+                    .map_err(|err| format!("{}: {}", self.path, err))?;
+            }
+            Output::Images { dir, format } => {
+                let path = Path::new(dir).join(format!("frame-{:06}.{}", self.frames, format.extension()));
+                save_image(&path.to_string_lossy(), *format, buffer, self.width, self.height)?;
+            }
+        }
+
+        self.frames += 1;
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<(), String> {
+        if let Output::Y4m(mut writer) = self.output {
+            writer.flush().map_err(|err| format!("{}: {}", self.path, err))?;
+        }
+
+        Ok(())
+    }
+}
+
+// Convierte a Y'CbCr BT.601 de rango limitado (el que asumen los codificadores) con croma submuestreado 2×2
+fn yuv420(buffer: &[u32], width: usize, height: usize) -> Vec<u8> {
+    let (chroma_width, chroma_height) = (width.div_ceil(2), height.div_ceil(2));
+    let mut frame = vec![0u8; width * height + 2 * chroma_width * chroma_height];
+    let (luma, chroma) = frame.split_at_mut(width * height);
+    let (cb_plane, cr_plane) = chroma.split_at_mut(chroma_width * chroma_height);
+
+    let rgb = |x: usize, y: usize| {
+        let pixel = buffer[y * width + x];
+        (((pixel >> 16) & 0xFF) as f32, ((pixel >> 8) & 0xFF) as f32, (pixel & 0xFF) as f32)
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            let (r, g, b) = rgb(x, y);
+            luma[y * width + x] = (16.0 + (65.481 * r + 128.553 * g + 24.966 * b) / 255.0).round() as u8;
+        }
+    }
+
+    for cy in 0..chroma_height {
+        for cx in 0..chroma_width {
+            // Promedio del bloque 2×2 (recortado en los bordes impares)
+            let (mut r, mut g, mut b, mut count) = (0.0, 0.0, 0.0, 0.0);
+            for y in (cy * 2)..(cy * 2 + 2).min(height) {
+                for x in (cx * 2)..(cx * 2 + 2).min(width) {
+                    let (pr, pg, pb) = rgb(x, y);
+                    r += pr;
+                    g += pg;
+                    b += pb;
+                    count += 1.0;
+                }
+            }
+            let (r, g, b) = (r / count, g / count, b / count);
+
+            let index = cy * chroma_width + cx;
+            cb_plane[index] = (128.0 + (-37.797 * r - 74.203 * g + 112.0 * b) / 255.0).round() as u8;
+            cr_plane[index] = (128.0 + (112.0 * r - 93.786 * g - 18.214 * b) / 255.0).round() as u8;
+        }
+    }
+
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: u32 = 0xFFFFFF;
+    const BLACK: u32 = 0x000000;
+    const RED: u32 = 0xFF0000;
+
+    #[test]
+    fn writes_a_y4m_stream_with_odd_sizes() {
+        let dir = std::env::temp_dir().join(format!("recording-test-{}", std::process::id()));
+        let dir = dir.to_string_lossy().into_owned();
+
+        // 3×3: el croma tiene 2×2 muestras y la última columna y fila sólo promedian lo que hay
+        let (width, height) = (3, 3);
+        let mut recorder = Recorder::start(&dir, RecordFormat::Y4m, width, height, Duration::from_micros(16667)).unwrap();
+        recorder.write_frame(&[WHITE, WHITE, BLACK, WHITE, WHITE, BLACK, WHITE, WHITE, BLACK]).unwrap();
+        recorder.write_frame(&[RED; 9]).unwrap();
+        let path = recorder.path.clone();
+        recorder.finish().unwrap();
+
+        let data = fs::read(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let header = b"YUV4MPEG2 W3 H3 F1000000:16667 Ip A1:1 C420jpeg\n";
+        assert_eq!(&data[..header.len()], header);
+
+        // Cada cuadro: marca, luma de 3×3 y dos planos de croma de 2×2
+        let frame_size = b"FRAME\n".len() + 9 + 2 * 4;
+        let frames = &data[header.len()..];
+        assert_eq!(frames.len(), 2 * frame_size);
+        let (first, second) = frames.split_at(frame_size);
+        assert!(first.starts_with(b"FRAME\n") && second.starts_with(b"FRAME\n"));
+
+        // Rango limitado: negro en 16, blanco en 235 y croma neutro en 128
+        assert_eq!(&first[6..15], &[235, 235, 16, 235, 235, 16, 235, 235, 16]);
+        assert_eq!(&first[15..19], &[128; 4]);
+        assert_eq!(&first[19..23], &[128; 4]);
+
+        // Rojo puro según BT.601
+        assert_eq!(&second[6..15], &[81; 9]);
+        assert_eq!(&second[15..19], &[90; 4]);
+        assert_eq!(&second[19..23], &[240; 4]);
+    }
+}
@@ -0,0 +1,33 @@
+// rect.rs
+
+// Rectángulo en píxeles con la esquina superior izquierda en (x, y)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: isize,
+    pub y: isize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub fn contains(&self, x: isize, y: isize) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width as isize && y < self.y + self.height as isize
+    }
+
+    // El mismo rectángulo en una pantalla escalada; los bordes se redondean para que rectángulos vecinos sigan juntos
+    pub fn scaled(&self, scale_x: f32, scale_y: f32) -> Rect {
+        let left = (self.x as f32 * scale_x).round() as isize;
+        let top = (self.y as f32 * scale_y).round() as isize;
+        let right = ((self.x + self.width as isize) as f32 * scale_x).round() as isize;
+        let bottom = ((self.y + self.height as isize) as f32 * scale_y).round() as isize;
+        Rect { x: left, y: top, width: (right - left).max(0) as usize, height: (bottom - top).max(0) as usize }
+    }
+
+    pub fn intersection(&self, other: &Rect) -> Rect {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = (self.x + self.width as isize).min(other.x + other.width as isize);
+        let bottom = (self.y + self.height as isize).min(other.y + other.height as isize);
+        Rect { x: left, y: top, width: (right - left).max(0) as usize, height: (bottom - top).max(0) as usize }
+    }
+}
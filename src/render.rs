@@ -3,36 +3,55 @@ use crate::vertex::Vertex;
 use crate::framebuffer::Framebuffer;
 use crate::triangule::triangle;
 use crate::shader::{vertex_shader, fragment_shader};
+use crate::bake::BakedSurface;
+use crate::ssao::AmbientOcclusion;
 
 use nalgebra_glm::{Mat4, Vec2, Vec3, Vec4, look_at, perspective};
 use fastnoise_lite::FastNoiseLite;
 use std::f32::consts::PI;
 
-pub struct Uniforms {
+// Campo de visión vertical de la cámara
+pub const FIELD_OF_VIEW: f32 = 45.0 * PI / 180.0;
+
+pub struct Uniforms<'a> {
     pub model_matrix: Mat4,
     pub view_matrix: Mat4,
     pub projection_matrix: Mat4,
     pub viewport_matrix: Mat4,
     pub time: u32,
-    pub noise: FastNoiseLite
+    pub noise: FastNoiseLite,
+    pub light_direction: Vec3, // Dirección normalizada hacia la estrella
+    pub camera_position: Vec3,
+    pub surface: Option<&'a BakedSurface>, // Superficie horneada; `None` usa el shader en vivo
+    pub previous_matrix: Mat4, // Proyección × vista × modelo del cuadro anterior, para el buffer de velocidad
+    pub ambient_occlusion: Option<&'a AmbientOcclusion>, // Oclusión del término ambiental, calculada antes para este modelo
 }
 
 pub fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
+    create_rect_viewport_matrix(0.0, 0.0, width, height)
+}
+
+// Lleva las coordenadas normalizadas al rectángulo con esquina superior izquierda en (`x`, `y`)
+pub fn create_rect_viewport_matrix(x: f32, y: f32, width: f32, height: f32) -> Mat4 {
     Mat4::new(
-        width /2.0, 0.0, 0.0, width / 2.0,
-        0.0, -height / 2.0, 0.0, height / 2.0,
+        width /2.0, 0.0, 0.0, x + width / 2.0,
+        0.0, -height / 2.0, 0.0, y + height / 2.0,
         0.0, 0.0, 1.0, 0.0,
         0.0, 0.0, 0.0, 1.0
     )
 }
 
 pub fn create_perspective_matrix(window_width: f32, window_height: f32) -> Mat4 {
-    let fov = 45.0 * PI / 180.0;
+    create_projection_matrix(window_width, window_height, FIELD_OF_VIEW)
+}
+
+pub fn create_projection_matrix(window_width: f32, window_height: f32, fov: f32) -> Mat4 {
     let aspect_ratio = window_width / window_height;
     let near = 1.0;
     let far = 1000.0;
 
-    perspective(fov, aspect_ratio, near, far)
+    // nalgebra-glm recibe primero la relación de aspecto y luego el campo de visión
+    perspective(aspect_ratio, fov, near, far)
 }
 
 pub fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
@@ -109,9 +128,26 @@ pub fn render(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array:
         let y = fragment.position.y as usize;
         if x < framebuffer.width && y < framebuffer.height {
             let shaded_color = fragment_shader(&fragment, &uniforms, number);
-            let color = shaded_color;
-            framebuffer.set_current_color(color);
-            framebuffer.point(x as isize, y as isize, fragment.depth);
+            framebuffer.set_current_linear_color(shaded_color);
+            framebuffer.point_with_velocity(x as isize, y as isize, fragment.depth, fragment.velocity);
+        }
+    }
+}
+
+// Solo la profundidad de los triángulos, sin sombrear, en un buffer de `width`×`height`.
+// Sirve de pasada previa para efectos que necesitan la profundidad antes de sombrear.
+pub fn render_depth(depth: &mut [f32], width: usize, height: usize, uniforms: &Uniforms, vertex_array: &[Vertex]) {
+    let transformed: Vec<Vertex> = vertex_array.iter().map(|vertex| vertex_shader(vertex, uniforms)).collect();
+
+    for tri in transformed.chunks_exact(3) {
+        for fragment in triangle(&tri[0], &tri[1], &tri[2]) {
+            if fragment.position.x < 0.0 || fragment.position.y < 0.0 {
+                continue;
+            }
+            let (x, y) = (fragment.position.x as usize, fragment.position.y as usize);
+            if x < width && y < height && fragment.depth < depth[y * width + x] {
+                depth[y * width + x] = fragment.depth;
+            }
         }
     }
 }
@@ -0,0 +1,72 @@
+// render_target.rs
+
+use crate::camera::Camera;
+use crate::framebuffer::Framebuffer;
+use crate::render::{create_perspective_matrix, create_view_matrix, create_viewport_matrix};
+use crate::texture::Texture;
+use nalgebra_glm::Mat4;
+
+// Framebuffer fuera de pantalla con su propia cámara. Se renderiza una vista de la escena en él
+// y luego se usa como textura (escalada, reflejada, como cualquier sprite) o se copia tal cual a otro framebuffer.
+pub struct RenderTarget {
+    pub framebuffer: Framebuffer,
+    pub camera: Camera,
+    texture: Texture,
+}
+
+impl RenderTarget {
+    pub fn new(width: usize, height: usize, camera: Camera) -> Self {
+        RenderTarget {
+            framebuffer: Framebuffer::new(width, height),
+            camera,
+            texture: Texture::new(width, height),
+        }
+    }
+
+    // No hace nada si el tamaño no cambia, así se puede llamar en cada cuadro
+    pub fn resize(&mut self, width: usize, height: usize) {
+        if self.framebuffer.width != width || self.framebuffer.height != height {
+            self.framebuffer.resize(width, height);
+        }
+    }
+
+    // Matrices de vista, proyección y viewport para la cámara y el tamaño de este target
+    pub fn matrices(&self) -> (Mat4, Mat4, Mat4) {
+        let (width, height) = (self.framebuffer.width as f32, self.framebuffer.height as f32);
+        (
+            create_view_matrix(self.camera.eye, self.camera.center, self.camera.up),
+            create_perspective_matrix(width, height),
+            create_viewport_matrix(width, height),
+        )
+    }
+
+    // Contenido actual como textura opaca. Se copia al llamar, así que va después de renderizar.
+    pub fn texture(&mut self) -> &Texture {
+        self.texture.width = self.framebuffer.width;
+        self.texture.height = self.framebuffer.height;
+        self.texture.pixels.clear();
+        self.texture.pixels.extend(self.framebuffer.buffer.iter().map(|pixel| 0xFF00_0000 | pixel));
+        &self.texture
+    }
+
+    // Copia píxel a píxel con la esquina superior izquierda en (`x`, `y`), recortando a `destination`
+    pub fn blit(&self, destination: &mut Framebuffer, x: isize, y: isize) {
+        let alpha = if destination.is_overlay() { 0xFF00_0000 } else { 0 };
+
+        for row in 0..self.framebuffer.height {
+            let dy = y + row as isize;
+            if dy < 0 || dy as usize >= destination.height {
+                continue;
+            }
+
+            for column in 0..self.framebuffer.width {
+                let dx = x + column as isize;
+                if dx < 0 || dx as usize >= destination.width {
+                    continue;
+                }
+                destination.buffer[dy as usize * destination.width + dx as usize] =
+                    alpha | (self.framebuffer.buffer[row * self.framebuffer.width + column] & 0x00FF_FFFF);
+            }
+        }
+    }
+}
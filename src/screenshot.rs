@@ -0,0 +1,98 @@
+// screenshot.rs
+
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::bmp::{write_bmp_file, BmpDepth, BmpFormat};
+use crate::framebuffer::Framebuffer;
+use crate::texture::Texture;
+
+// Formato de archivo de las capturas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageFormat {
+    Bmp,
+    Png,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Bmp => "bmp",
+            ImageFormat::Png => "png",
+        }
+    }
+}
+
+// Guarda `buffer` como `screenshot-AAAAMMDD-HHMMSS-mmm.<ext>` dentro de `dir` y devuelve la ruta
+pub fn save_screenshot(dir: &str, format: ImageFormat, buffer: &[u32], width: usize, height: usize) -> Result<String, String> {
+    fs::create_dir_all(dir).map_err(|err| format!("{}: {}", dir, err))?;
+
+    let name = format!("screenshot-{}.{}", timestamp(), format.extension());
+    let path = Path::new(dir).join(name).to_string_lossy().into_owned();
+    save_image(&path, format, buffer, width, height)?;
+
+    Ok(path)
+}
+
+// El buffer de la ventana no usa el byte alto, así que se guarda opaco
+pub fn save_image(path: &str, format: ImageFormat, buffer: &[u32], width: usize, height: usize) -> Result<(), String> {
+    let pixels: Vec<u32> = buffer.iter().map(|pixel| pixel | 0xFF00_0000).collect();
+
+    match format {
+        ImageFormat::Bmp => write_bmp_file(path, &pixels, width, height, BmpFormat { depth: BmpDepth::Rgb24, ..BmpFormat::default() }).map_err(|err| format!("{}: {}", path, err)),
+        ImageFormat::Png => Texture::from_pixels(width, height, pixels).save(path).map_err(|err| format!("{}: {}", path, err)),
+    }
+}
+
+// Promedia bloques de `factor`×`factor` píxeles de `source` en `target`
+pub fn downsample(source: &Framebuffer, target: &mut Framebuffer, factor: usize) {
+    let samples = (factor * factor) as u32;
+
+    for y in 0..target.height {
+        for x in 0..target.width {
+            let mut sum = [0u32; 4];
+            for sy in 0..factor {
+                let row = (y * factor + sy) * source.width;
+                for sx in 0..factor {
+                    let pixel = source.buffer[row + x * factor + sx];
+                    for (channel, total) in sum.iter_mut().enumerate() {
+                        *total += (pixel >> (channel * 8)) & 0xFF;
+                    }
+                }
+            }
+
+            target.buffer[y * target.width + x] = sum
+                .iter()
+                .enumerate()
+                .fold(0, |pixel, (channel, total)| pixel | (((total + samples / 2) / samples) << (channel * 8)));
+        }
+    }
+}
+
+// Fecha y hora UTC actuales como `AAAAMMDD-HHMMSS-mmm`
+pub fn timestamp() -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = now.as_secs();
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    let time = seconds % 86_400;
+
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}-{:03}",
+        year, month, day, time / 3600, time / 60 % 60, time % 60, now.subsec_millis()
+    )
+}
+
+// Días desde 1970-01-01 a fecha del calendario gregoriano (algoritmo de Howard Hinnant)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
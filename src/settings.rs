@@ -0,0 +1,271 @@
+// settings.rs
+
+use std::fmt;
+use std::fs;
+use std::ops::RangeInclusive;
+use crate::skybox::StarLayer;
+use crate::screenshot::ImageFormat;
+use crate::recording::RecordFormat;
+use crate::color::{BlendMode, Color};
+use crate::postprocess::{Effect, Palette};
+use crate::viewport::Layout;
+
+// Tipo de fondo para el skybox
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SkyboxKind {
+    Stars,      // Estrellas aleatorias
+    Procedural, // Banda galáctica y nebulosas
+    Image,      // Cube map o panorama cargado desde disco
+    Catalog,    // Estrellas reales de un catálogo CSV
+}
+
+// Ajustes de la escena leídos de un archivo `clave = valor`.
+// Las claves que faltan conservan su valor por defecto.
+pub struct Settings {
+    pub skybox: SkyboxKind,
+    pub skybox_path: String,
+    pub sky_seed: u64,
+    pub star_catalog: String,
+    pub limiting_magnitude: f32,
+    pub star_layers: Vec<StarLayer>,
+    pub twinkle: f32,
+    pub fallback_font: String, // Vacío para no usar fuente de respaldo
+    pub hud_opacity: f32,
+    pub hud_blend: BlendMode,
+    pub screenshot_dir: String,
+    pub screenshot_format: ImageFormat,
+    pub screenshot_scale: usize, // 1 = resolución de la ventana; 2 a 4 vuelve a renderizar la escena más grande
+    pub screenshot_hud: bool,
+    pub record_dir: String,
+    pub record_format: RecordFormat,
+    pub render_scale: f32, // Fracción del tamaño de la ventana a la que se renderiza la escena 3D
+    pub post_effects: Vec<String>, // Efectos de post-proceso activos, en el orden en que se aplican
+    pub vignette_strength: f32,
+    pub vignette_radius: f32,
+    pub chromatic_aberration: f32,
+    pub film_grain: f32,
+    pub crt_curvature: f32,
+    pub crt_scanlines: f32,
+    pub dither_palette: Palette,
+    pub lens_flare: f32, // Intensidad de reflejos y estallido de la estrella; 0 los desactiva
+    pub god_rays: f32,   // Intensidad de los rayos de luz; 0 los desactiva
+    pub motion_blur: bool, // Estado inicial del desenfoque de movimiento (V lo alterna)
+    pub shutter_angle: f32, // Fracción del movimiento del cuadro que se desenfoca, en grados (0 a 360)
+    pub ssao: bool, // Oclusión ambiental en la nave
+    pub ssao_radius: f32,
+    pub ssao_intensity: f32,
+    pub layout: Layout, // Distribución inicial de las vistas (M pasa a la siguiente)
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            skybox: SkyboxKind::Procedural,
+            skybox_path: "skybox".to_string(),
+            sky_seed: 2024,
+            star_catalog: "catalogs/bright_stars.csv".to_string(),
+            limiting_magnitude: 6.5,
+            star_layers: StarLayer::defaults(),
+            twinkle: 0.0,
+            fallback_font: "fonts/DejaVuSans.ttf".to_string(),
+            hud_opacity: 1.0,
+            hud_blend: BlendMode::Normal,
+            screenshot_dir: "screenshots".to_string(),
+            screenshot_format: ImageFormat::Png,
+            screenshot_scale: 1,
+            screenshot_hud: true,
+            record_dir: "recordings".to_string(),
+            record_format: RecordFormat::Y4m,
+            render_scale: 1.0,
+            post_effects: Vec::new(),
+            vignette_strength: 0.5,
+            vignette_radius: 0.4,
+            chromatic_aberration: 0.004,
+            film_grain: 0.05,
+            crt_curvature: 0.08,
+            crt_scanlines: 0.3,
+            dither_palette: Palette::Levels(4),
+            lens_flare: 1.0,
+            god_rays: 0.6,
+            motion_blur: false,
+            shutter_angle: 180.0,
+            ssao: true,
+            ssao_radius: 15.0,
+            ssao_intensity: 1.0,
+            layout: Layout::Chase,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load(path: &str) -> Self {
+        let mut settings = Settings::default();
+
+        let Ok(contents) = fs::read_to_string(path) else {
+            return settings;
+        };
+
+        for (line_number, line) in contents.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let result = match line.split_once('=') {
+                Some((key, value)) => settings.set(key.trim(), value.trim()),
+                None => Err("expected `key = value`".to_string()),
+            };
+
+            if let Err(err) = result {
+                eprintln!("{}:{}: {}", path, line_number + 1, err);
+            }
+        }
+
+        settings
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "skybox" => {
+                self.skybox = match value {
+                    "stars" => SkyboxKind::Stars,
+                    "procedural" => SkyboxKind::Procedural,
+                    "image" => SkyboxKind::Image,
+                    "catalog" => SkyboxKind::Catalog,
+                    _ => return Err(format!("unknown skybox `{}`", value)),
+                }
+            }
+            "skybox_path" => self.skybox_path = value.to_string(),
+            "sky_seed" => self.sky_seed = parse(key, value)?,
+            "star_catalog" => self.star_catalog = value.to_string(),
+            "limiting_magnitude" => self.limiting_magnitude = parse(key, value)?,
+            "star_layers" => self.star_layers = parse_star_layers(value)?,
+            "twinkle" => self.twinkle = parse(key, value)?,
+            "fallback_font" => self.fallback_font = value.to_string(),
+            "hud_opacity" => self.hud_opacity = parse(key, value)?,
+            "hud_blend" => self.hud_blend = value.parse()?,
+            "screenshot_dir" => self.screenshot_dir = value.to_string(),
+            "screenshot_format" => self.screenshot_format = parse_image_format(value)?,
+            "screenshot_scale" => self.screenshot_scale = parse_in_range(key, value, 1..=4)?,
+            "screenshot_hud" => self.screenshot_hud = parse(key, value)?,
+            "render_scale" => self.render_scale = parse_in_range(key, value, 0.25..=1.0)?,
+            "post_effects" => {
+                self.post_effects = value
+                    .split(',')
+                    .map(|effect| effect.trim().to_string())
+                    .filter(|effect| !effect.is_empty())
+                    .collect();
+                if let Some(effect) = self.post_effects.iter().find(|effect| !POST_EFFECTS.contains(&effect.as_str())) {
+                    return Err(format!("unknown post effect `{}`", effect));
+                }
+            }
+            "vignette_strength" => self.vignette_strength = parse(key, value)?,
+            "vignette_radius" => self.vignette_radius = parse(key, value)?,
+            "chromatic_aberration" => self.chromatic_aberration = parse(key, value)?,
+            "film_grain" => self.film_grain = parse(key, value)?,
+            "crt_curvature" => self.crt_curvature = parse(key, value)?,
+            "crt_scanlines" => self.crt_scanlines = parse(key, value)?,
+            "dither_palette" => self.dither_palette = parse_palette(value)?,
+            "lens_flare" => self.lens_flare = parse(key, value)?,
+            "god_rays" => self.god_rays = parse(key, value)?,
+            "ssao" => self.ssao = parse(key, value)?,
+            "ssao_radius" => self.ssao_radius = parse(key, value)?,
+            "ssao_intensity" => self.ssao_intensity = parse(key, value)?,
+            "motion_blur" => self.motion_blur = parse(key, value)?,
+            "layout" => self.layout = value.parse()?,
+            "shutter_angle" => self.shutter_angle = parse_in_range(key, value, 0.0..=360.0)?,
+            "record_dir" => self.record_dir = value.to_string(),
+            "record_format" => {
+                self.record_format = match value {
+                    "y4m" => RecordFormat::Y4m,
+                    _ => RecordFormat::Images(parse_image_format(value)?),
+                }
+            }
+            _ => return Err(format!("unknown setting `{}`", key)),
+        }
+
+        Ok(())
+    }
+
+    // Cadena de post-proceso según `post_effects` y los parámetros de cada efecto
+    pub fn post_process_chain(&self) -> Vec<Effect> {
+        self.post_effects
+            .iter()
+            .filter_map(|effect| match effect.as_str() {
+                "vignette" => Some(Effect::Vignette { strength: self.vignette_strength, radius: self.vignette_radius }),
+                "chromatic_aberration" => Some(Effect::ChromaticAberration { offset: self.chromatic_aberration }),
+                "film_grain" => Some(Effect::FilmGrain { amount: self.film_grain }),
+                "crt" => Some(Effect::Crt { curvature: self.crt_curvature, scanlines: self.crt_scanlines }),
+                "dither" => Some(Effect::Dither { palette: self.dither_palette.clone() }),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+const POST_EFFECTS: [&str; 5] = ["vignette", "chromatic_aberration", "film_grain", "crt", "dither"];
+
+// Niveles por canal (`4`), una paleta conocida o colores separados por comas.
+// Los colores van sin `#` porque en el archivo `#` empieza un comentario.
+fn parse_palette(value: &str) -> Result<Palette, String> {
+    let colors = match value {
+        "gameboy" => "0f380f, 306230, 8bac0f, 9bbc0f",
+        "cga" => "black, 55ffff, ff55ff, white",
+        _ => match value.parse() {
+            Ok(levels) => return Ok(Palette::Levels(levels)),
+            Err(_) => value,
+        },
+    };
+
+    colors
+        .split(',')
+        .map(Color::from_hex_str)
+        .collect::<Result<Vec<Color>, String>>()
+        .map(Palette::Colors)
+}
+
+fn parse<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{}` for `{}`", value, key))
+}
+
+// Como `parse`, pero un valor fuera de `range` es un error y el ajuste queda como estaba
+fn parse_in_range<T>(key: &str, value: &str, range: RangeInclusive<T>) -> Result<T, String>
+where
+    T: std::str::FromStr + PartialOrd + fmt::Display,
+{
+    let parsed = parse(key, value)?;
+    if !range.contains(&parsed) {
+        return Err(format!("`{}` must be between {} and {}", key, range.start(), range.end()));
+    }
+    Ok(parsed)
+}
+
+fn parse_image_format(value: &str) -> Result<ImageFormat, String> {
+    match value {
+        "bmp" => Ok(ImageFormat::Bmp),
+        "png" => Ok(ImageFormat::Png),
+        _ => Err(format!("unknown image format `{}`", value)),
+    }
+}
+
+// `count@distance` separados por comas; `inf` para una capa sin paralaje
+fn parse_star_layers(value: &str) -> Result<Vec<StarLayer>, String> {
+    value
+        .split(',')
+        .map(|layer| {
+            let (count, distance) = layer
+                .trim()
+                .split_once('@')
+                .ok_or_else(|| format!("star layer `{}` must be `count@distance`", layer.trim()))?;
+
+            let distance = match distance.trim() {
+                "inf" => f32::INFINITY,
+                distance => parse("star_layers", distance)?,
+            };
+
+            Ok(StarLayer { count: parse("star_layers", count.trim())?, distance })
+        })
+        .collect()
+}
@@ -2,8 +2,13 @@
 use nalgebra_glm::{Vec2, Vec3, Vec4, Mat3, mat4_to_mat3, dot};
 use crate::vertex::Vertex;
 use crate::render::Uniforms;
-use crate::color::Color;
+use crate::color::{srgb_to_linear, BlendMode, Color, LinearColor};
 use crate::fragment::Fragment;
+use fastnoise_lite::FastNoiseLite;
+
+// Radio de la esfera en unidades de ruido para las capas estáticas, tanto
+// al hornear como al sombrear en vivo.
+const SURFACE_SCALE: f32 = 150.0;
 
 pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
     //Transform position
@@ -27,6 +32,15 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
     // apply viewport matrix
     let screen_position = uniforms.viewport_matrix * ndc_position;
 
+    // Misma transformación con las matrices del cuadro anterior; detrás de la cámara se toma como quieto
+    let previous = uniforms.previous_matrix * position;
+    let previous_position = if previous.w > 0.0 {
+        let previous_screen = uniforms.viewport_matrix * Vec4::new(previous.x / previous.w, previous.y / previous.w, 0.0, 1.0);
+        Vec2::new(previous_screen.x, previous_screen.y)
+    } else {
+        Vec2::new(screen_position.x, screen_position.y)
+    };
+
     //Transform normal
     let model_mat3 = mat4_to_mat3(&uniforms.model_matrix);
     let normal_matrix = model_mat3.transpose().try_inverse().unwrap_or(Mat3::identity());
@@ -41,10 +55,11 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
         color: vertex.color,
         transformed_position: Vec3::new(screen_position.x, screen_position.y, screen_position.z),
         transformed_normal,
+        previous_position,
     }
 }
 
-pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms, number: u8) -> Color {
+pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms, number: u8) -> LinearColor {
     if number == 0 {
         spaceship_shader(fragment, uniforms)
     } else if number == 1 {
@@ -58,14 +73,14 @@ pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms, number: u8) ->
     } else if number == 5 {
         lava_shader(fragment, uniforms)
     } else {
-        planet_shader(fragment, uniforms)
+        planet_shader(fragment, uniforms, number)
     }
 }
 
-fn hull_layer(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn hull_layer(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
     // Color base del casco metálico
-    let base_color = Color::new(80, 80, 100); // Gris metálico
-    let highlight_color = Color::new(200, 200, 220); // Resaltado
+    let base_color = Color::new(80, 80, 100).to_linear(); // Gris metálico
+    let highlight_color = Color::new(200, 200, 220).to_linear(); // Resaltado
 
     // Simulación de reflejos metálicos con ruido
     let noise_value = uniforms.noise.get_noise_2d(fragment.position.x * 3.0, fragment.position.y * 3.0);
@@ -79,9 +94,9 @@ fn hull_layer(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     metallic_shine * (0.6 + 0.4 * specular_intensity) * fragment.intensity
 }
 
-fn engine_glow_layer(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn engine_glow_layer(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
     // Brillo de los motores (azul vibrante)
-    let engine_glow_color = Color::new(50, 150, 255); // Azul eléctrico
+    let engine_glow_color = Color::new(50, 150, 255).to_linear(); // Azul eléctrico
     let noise_value = uniforms.noise.get_noise_3d(
         fragment.position.x * 10.0,
         fragment.position.y * 10.0,
@@ -92,9 +107,9 @@ fn engine_glow_layer(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     engine_glow_color * glow_intensity * 0.8
 }
 
-fn scratches_layer(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn scratches_layer(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
     // Simulación de rayones en el casco
-    let scratch_color = Color::new(30, 30, 40); // Oscuro, casi negro
+    let scratch_color = Color::new(30, 30, 40).to_linear(); // Oscuro, casi negro
     let noise_value = uniforms.noise.get_noise_2d(fragment.position.x * 20.0, fragment.position.y * 20.0);
 
     // Sólo aplicamos rayones en ciertas áreas
@@ -103,7 +118,7 @@ fn scratches_layer(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     scratch_color * scratch_intensity
 }
 
-fn spaceship_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn spaceship_shader(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
     // Capa del casco metálico
     let hull_color = hull_layer(fragment, uniforms);
 
@@ -118,13 +133,19 @@ fn spaceship_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let diffuse_intensity = light_direction.dot(&fragment_normal).clamp(0.0, 1.0);
 
     let ambient_intensity = 0.5;
-    let ambient_color = Color::new(80,80,100);
+    // La oclusión ambiental oscurece las grietas y uniones del casco
+    let ambient_color = Color::new(80,80,100).to_linear() * ambient_occlusion(fragment, uniforms);
 
     let lighting = ambient_color * ambient_intensity + hull_color * diffuse_intensity;
 
-    // Combinamos las capas
-    let base_with_scratches = lighting.blend_with(&scratches_color);
-    let blended_lighting = base_with_scratches.blend_with(&engine_glow_color);
+    // Capas sobre la iluminación, de abajo hacia arriba
+    let layers = [
+        (scratches_color, BlendMode::Normal, 0.5),
+        (engine_glow_color, BlendMode::Normal, 0.5),
+    ];
+    let blended_lighting = layers
+        .iter()
+        .fold(lighting, |color, (layer, mode, alpha)| color.blend(layer, *mode, *alpha));
 
     let min_intensity = 0.2;
 
@@ -133,37 +154,45 @@ fn spaceship_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     final_color
 }
 
-fn ocean_layer(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn ambient_occlusion(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+    uniforms
+        .ambient_occlusion
+        .map_or(1.0, |occlusion| occlusion.factor(fragment.position.x as usize, fragment.position.y as usize))
+}
+
+fn ocean_color(noise_value: f32) -> LinearColor {
     // Color base para el océano (azul alienígena)
-    let ocean_color = Color::new(0, 0, 150); 
-    let noise_value = uniforms.noise.get_noise_2d(fragment.position.x * 5.0, fragment.position.y * 5.0);
+    let ocean_color = Color::new(0, 0, 150).to_linear(); 
     let intensity = (0.7 + 0.3 * noise_value) as f32; // Intensidad variada por el ruido
     ocean_color * intensity 
 }
 
-fn continents_layer(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn continents_color(noise_value: f32) -> LinearColor {
     // Colores para el terreno rocoso
-    let base_color = Color::new(150, 75, 0);  // Color tierra marrón
-    let rocky_color = Color::new(100, 100, 100); // Color gris para áreas rocosas
+    let base_color = Color::new(150, 75, 0).to_linear();  // Color tierra marrón
+    let rocky_color = Color::new(100, 100, 100).to_linear(); // Color gris para áreas rocosas
 
-    // Ruido para simular montañas y valles
-    let noise_value = uniforms.noise.get_noise_2d(fragment.position.x * 2.0, fragment.position.y * 2.0);
     let terrain_color = base_color.lerp(&rocky_color, (noise_value * 0.5 + 0.5) as f32);
 
     terrain_color 
 }
 
-fn atmosphere_layer(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+// La atmósfera no se anima, pero va sobre las nubes; se evalúa por fragmento
+// con el mismo ruido fijo a la esfera que las capas horneadas
+fn atmosphere_layer(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
+    atmosphere_color(sphere_noise(&uniforms.noise, &fragment.vertex_position, 0.5))
+}
+
+fn atmosphere_color(noise_value: f32) -> LinearColor {
     // Colores de la atmósfera (suave brillo)
-    let atmosphere_color = Color::new(50, 100, 200); // Un azul brillante
-    let noise_value = uniforms.noise.get_noise_2d(fragment.position.x * 0.5, fragment.position.y * 0.5);
+    let atmosphere_color = Color::new(50, 100, 200).to_linear(); // Un azul brillante
     let intensity = (0.5 + 0.5 * noise_value) as f32; // Atmósfera con variaciones sutiles
     atmosphere_color * intensity 
 }
 
-fn clouds_layer(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn clouds_layer(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
     // Colores de las nubes (blanco y suave)
-    let cloud_color = Color::new(255, 255, 255);
+    let cloud_color = Color::new(255, 255, 255).to_linear();
     let cloud_noise = uniforms.noise.get_noise_3d(
         fragment.position.x * 2.0 + uniforms.time as f32 * 0.1,
         fragment.position.y * 2.0 + uniforms.time as f32 * 0.1,
@@ -174,12 +203,9 @@ fn clouds_layer(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     cloud_color * cloud_intensity * 0.7
 }
 
-fn planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    // Capa del océano
-    let ocean_color = ocean_layer(fragment, uniforms);
-    
-    // Capa de los continentes (terreno rocoso)
-    let continents_color = continents_layer(fragment, uniforms);
+fn planet_shader(fragment: &Fragment, uniforms: &Uniforms, number: u8) -> LinearColor {
+    // Océano y continentes (terreno rocoso)
+    let (base_color, _) = static_layers(fragment, uniforms, number);
 
     // Capa de la atmósfera
     let atmosphere_color = atmosphere_layer(fragment, uniforms);
@@ -187,39 +213,38 @@ fn planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     // Capa de nubes
     let clouds_color = clouds_layer(fragment, uniforms);
 
-    // Combinamos las capas en un orden adecuado
-    let base_color = if continents_color.is_equal(&ocean_color) {
-        continents_color
-    } else {
-        ocean_color
-    };
-
     // Primero combinamos las nubes con el planeta
-    let planet_with_clouds = clouds_color.blend_with(&base_color);
+    let planet_with_clouds = clouds_color.lerp(&base_color, 0.5);
 
     // Luego combinamos la atmósfera
-    let final_color = atmosphere_color.blend_with(&planet_with_clouds);
+    let final_color = atmosphere_color.lerp(&planet_with_clouds, 0.5);
 
     // Devuelve el color final con la intensidad de fragmento
     final_color 
 }
 
-fn ocean_layer2(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let ocean_color = Color::new(0, 105, 148);
-    let noise_value = uniforms.noise.get_noise_2d(fragment.position.x * 10.0, fragment.position.y * 10.0);
+fn planet_surface(ocean_color: LinearColor, continents_color: LinearColor) -> LinearColor {
+    if continents_color == ocean_color {
+        continents_color
+    } else {
+        ocean_color
+    }
+}
+
+fn ocean_color2(noise_value: f32) -> LinearColor {
+    let ocean_color = Color::new(0, 105, 148).to_linear();
     let intensity = (0.8 + 0.2 * noise_value) as f32;
     ocean_color * intensity 
 }
 
-fn continents_layer2(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let land_color = Color::new(34,139,34);
-    let desert_color = Color::new(194, 178, 128);
-    let noise_value = uniforms.noise.get_noise_2d(fragment.position.x * 5.0, fragment.position.y * 5.0);
+fn continents_color2(noise_value: f32) -> LinearColor {
+    let land_color = Color::new(34,139,34).to_linear();
+    let desert_color = Color::new(194, 178, 128).to_linear();
     land_color.lerp(&desert_color, (noise_value * 0.5 + 0.5) as f32) 
 }
 
-fn clouds_layer2(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let cloud_color = Color::new(255,255,255);
+fn clouds_layer2(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
+    let cloud_color = Color::new(255,255,255).to_linear();
     let cloud_noise = uniforms.noise.get_noise_3d(
         fragment.position.x * 2.0 + uniforms.time as f32 * 0.1,
         fragment.position.y * 2.0 + uniforms.time as f32 * 0.1,
@@ -230,31 +255,81 @@ fn clouds_layer2(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     cloud_color * cloud_intensity * 0.7
 }
 
-fn earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let ocean_color = ocean_layer2(fragment, uniforms);
-    let continents_color = continents_layer2(fragment, uniforms);
-    let clouds_color = clouds_layer2(fragment, uniforms);
+fn earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
+    let (surface, height) = static_layers(fragment, uniforms, 1);
+    let land = land_mask(height);
 
-    if !continents_color.is_equal(&ocean_color) {
-        let land_or_ocean = continents_color;
+    let with_clouds = earth_with_clouds(fragment, uniforms, surface);
 
-        if clouds_color.is_black() {
-            land_or_ocean
-        } else {
-            clouds_color.blend_with(&land_or_ocean)
-        }
+    earth_lighting(fragment, uniforms, with_clouds, land)
+}
+
+// Máscara suave de tierra a partir de la altura normalizada del terreno
+fn land_mask(height: f32) -> f32 {
+    smoothstep(0.48, 0.52, height)
+}
+
+fn earth_surface(ocean_color: LinearColor, continents_color: LinearColor, land: f32) -> LinearColor {
+    ocean_color.lerp(&continents_color, land)
+}
+
+// Iluminación del lado día/noche: terminador suave, luces de ciudades en la
+// tierra del lado nocturno y reflejo especular del sol sobre el océano.
+// Los niveles de ambiente, lado de día y crepúsculo se ajustaron en espacio gamma;
+// se pasan a lineal para conservar el mismo brillo en pantalla.
+fn earth_lighting(fragment: &Fragment, uniforms: &Uniforms, surface: LinearColor, land: f32) -> LinearColor {
+    let normal = fragment.normal.normalize();
+    let n_dot_l = dot(&normal, &uniforms.light_direction);
+
+    let day = smoothstep(-0.15, 0.2, n_dot_l);
+    let terminator = 1.0 - (n_dot_l / 0.15).abs().min(1.0);
+
+    let ambient = srgb_to_linear(0.06);
+    let diffuse = n_dot_l.max(0.0);
+    let lit_surface = surface * (ambient + (1.0 - ambient) * diffuse.max(day * srgb_to_linear(0.35)));
+
+    // Banda cálida en el terminador
+    let dusk_color = Color::new(255, 120, 60).to_linear() * (terminator * srgb_to_linear(0.12));
+
+    // Reflejo del sol en el océano (Blinn-Phong)
+    let world_position = uniforms.model_matrix * Vec4::new(
+        fragment.vertex_position.x,
+        fragment.vertex_position.y,
+        fragment.vertex_position.z,
+        1.0,
+    );
+    let view_direction = (uniforms.camera_position - world_position.xyz()).normalize();
+    let half_vector = (uniforms.light_direction + view_direction).normalize();
+    let specular = dot(&normal, &half_vector).max(0.0).powf(64.0) * (1.0 - land) * day;
+    let glint_color = Color::new(255, 245, 220).to_linear() * specular;
+
+    // Luces de ciudades: ruido fijo a la esfera, sólo en tierra y de noche
+    let p = fragment.vertex_position.normalize() * SURFACE_SCALE * 20.0;
+    let city_noise = uniforms.noise.get_noise_3d(p.x, p.y, p.z);
+    let city_lights = smoothstep(0.1, 0.3, city_noise) * land * (1.0 - day);
+    let city_color = Color::new(255, 200, 120).to_linear() * city_lights;
+
+    lit_surface + dusk_color + glint_color + city_color
+}
+
+pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn earth_with_clouds(fragment: &Fragment, uniforms: &Uniforms, land_or_ocean: LinearColor) -> LinearColor {
+    let clouds_color = clouds_layer2(fragment, uniforms);
+
+    if clouds_color.is_black() {
+        land_or_ocean
     } else {
-        if clouds_color.is_black() {
-            ocean_color
-        } else {
-            clouds_color.blend_with(&ocean_color)
-        }
+        clouds_color.lerp(&land_or_ocean, 0.5)
     }
 }
 
-pub fn magma_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let bright_color = Color::new(255, 100, 0);
-    let dark_color = Color::new(50,10,0);
+pub fn magma_shader(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
+    let bright_color = Color::new(255, 100, 0).to_linear();
+    let dark_color = Color::new(50,10,0).to_linear();
 
     let position = Vec3::new(
         fragment.vertex_position.x,
@@ -288,8 +363,12 @@ pub fn magma_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     color  
 }
 
-pub fn combined_ice_cloud_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let ice_color = ice_shader(fragment, uniforms);
+pub fn combined_ice_cloud_shader(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
+    let (ice_color, _) = static_layers(fragment, uniforms, 3);
+    ice_with_clouds(fragment, uniforms, ice_color)
+}
+
+fn ice_with_clouds(fragment: &Fragment, uniforms: &Uniforms, ice_color: LinearColor) -> LinearColor {
     let cloud_color = cloud_ice_shader(fragment, uniforms);
 
     if !cloud_color.is_black() {
@@ -299,7 +378,7 @@ pub fn combined_ice_cloud_shader(fragment: &Fragment, uniforms: &Uniforms) -> Co
     }
 }
 
-pub fn cloud_ice_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn cloud_ice_shader(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
     let uv = fragment.position;
 
     let speed = 0.01;
@@ -314,14 +393,10 @@ pub fn cloud_ice_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
         cloud_intensity as i32,
         cloud_intensity as i32,
         cloud_intensity as i32,
-    )
+    ).to_linear()
 }
 
-pub fn ice_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let uv = fragment.position;
-
-    let noise_value = uniforms.noise.get_noise_2d(uv.x * 4.0, uv.y * 4.0);
-
+fn ice_color(noise_value: f32) -> LinearColor {
     let blue_intensity = (180.0 + 60.0 * (noise_value * 0.5 + 0.5)).clamp(0.0, 255.0);
     let white_intensity = (200.0 + 30.0 * (noise_value * 0.5 + 0.5)).clamp(180.0, 255.0);
 
@@ -329,10 +404,10 @@ pub fn ice_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
         white_intensity as i32,
         (white_intensity * 0.95) as i32,
         blue_intensity as i32,
-    )
+    ).to_linear()
 }
 
-pub fn asteroid_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn asteroid_shader(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
     let uv = fragment.position;
 
     let noise_value = uniforms.noise.get_noise_2d(uv.x * 10.0, uv.y * 10.0);
@@ -343,26 +418,27 @@ pub fn asteroid_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
         base_color_intensity as i32,
         (base_color_intensity * 0.9) as i32,
         (base_color_intensity * 0.7) as i32,
-    )
+    ).to_linear()
 }
 
-pub fn star_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let uv = fragment.position;
-    let noise_value = uniforms.noise.get_noise_2d(uv.x * 5.0, uv.y * 5.0);
+pub fn star_shader(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
+    static_layers(fragment, uniforms, 4).0
+}
 
+fn star_color(noise_value: f32) -> LinearColor {
     let red_intensity = 255 - (50.0 * (noise_value * 0.5 + 0.5)).clamp(0.0, 50.0) as i32;
     let green_intensity = 180 - (50.0 * (noise_value * 0.5 + 0.5)).clamp(0.0, 50.0) as i32;
    Color::new(
         (red_intensity ) as i32,
         (green_intensity ) as i32,
         (40.0 ) as i32,
-    ) 
+    ).to_linear()
 }
 
-fn lava_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn lava_shader(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
   // Base colors for the lava effect
-  let bright_color = Color::new(255, 240, 0); // Bright orange (lava-like)
-  let dark_color = Color::new(130, 20, 0);   // Darker red-orange
+  let bright_color = Color::new(255, 240, 0).to_linear(); // Bright orange (lava-like)
+  let dark_color = Color::new(130, 20, 0).to_linear();   // Darker red-orange
 
   // Get fragment position
   let position = Vec3::new(
@@ -398,3 +474,52 @@ fn lava_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
 
   color 
 }
+
+// Evalúa las capas estáticas de un cuerpo en un punto de la esfera unitaria.
+// Devuelve el color (en sRGB, como se guarda en la textura horneada) y la altura
+// normalizada [0, 1], o `None` si el cuerpo está completamente animado y debe sombrearse en vivo.
+pub fn surface_color(number: u8, noise: &FastNoiseLite, direction: &Vec3) -> Option<(Color, f32)> {
+    let sample = |factor: f32| sphere_noise(noise, direction, factor);
+
+    match number {
+        1 => {
+            let continents = sample(5.0);
+            let height = continents * 0.5 + 0.5;
+            let color = earth_surface(ocean_color2(sample(10.0)), continents_color2(continents), land_mask(height));
+            Some((color.to_srgb(), height))
+        }
+        3 => {
+            let ice = sample(4.0);
+            Some((ice_color(ice).to_srgb(), ice * 0.5 + 0.5))
+        }
+        4 => {
+            let star = sample(5.0);
+            Some((star_color(star).to_srgb(), star * 0.5 + 0.5))
+        }
+        0 | 2 | 5 => None,
+        _ => {
+            let continents = sample(2.0);
+            let base_color = planet_surface(ocean_color(sample(5.0)), continents_color(continents));
+            Some((base_color.to_srgb(), continents * 0.5 + 0.5))
+        }
+    }
+}
+
+// Ruido 3D fijo a la esfera: `direction` se proyecta al radio `SURFACE_SCALE`
+fn sphere_noise(noise: &FastNoiseLite, direction: &Vec3, factor: f32) -> f32 {
+    let p = direction.normalize() * SURFACE_SCALE * factor;
+    noise.get_noise_3d(p.x, p.y, p.z)
+}
+
+// Capas estáticas de un cuerpo: se leen de la textura horneada si existe o se
+// evalúan en vivo con `surface_color`; ambos caminos dan el mismo aspecto y
+// sólo las nubes se siguen animando por fragmento.
+fn static_layers(fragment: &Fragment, uniforms: &Uniforms, number: u8) -> (LinearColor, f32) {
+    let direction = &fragment.vertex_position;
+
+    let (color, height) = match uniforms.surface {
+        Some(surface) => (surface.sample(direction), surface.height_at(direction)),
+        None => surface_color(number, &uniforms.noise, direction).unwrap_or((Color::new(0, 0, 0), 0.0)),
+    };
+    (color.to_linear(), height)
+}
@@ -0,0 +1,456 @@
+// shapes.rs
+
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::rect::Rect;
+use nalgebra_glm::Vec2;
+use std::f32::consts::TAU;
+
+// Terminación de los extremos de una línea gruesa
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineCap {
+    Butt,   // Termina justo en el extremo
+    Square, // Se extiende medio ancho más allá del extremo
+    Round,  // Semicírculo en cada extremo
+}
+
+// Trazo para líneas y contornos. `dash` es (largo del trazo, largo del hueco) en píxeles.
+#[derive(Debug, Clone, Copy)]
+pub struct Stroke {
+    pub color: Color,
+    pub alpha: f32,
+    pub width: f32,
+    pub cap: LineCap,
+    pub dash: Option<(f32, f32)>,
+}
+
+impl Default for Stroke {
+    fn default() -> Self {
+        Stroke {
+            color: Color::new(255, 255, 255),
+            alpha: 1.0,
+            width: 1.0,
+            cap: LineCap::Butt,
+            dash: None,
+        }
+    }
+}
+
+// Primitivas 2D suavizadas para el HUD. Todas se mezclan con alfa a profundidad 0
+// y respetan el rectángulo de recorte del framebuffer.
+pub trait Shapes {
+    fn line_aa(&mut self, from: Vec2, to: Vec2, color: Color, alpha: f32);
+    fn stroke_line(&mut self, from: Vec2, to: Vec2, stroke: &Stroke);
+    fn fill_circle(&mut self, center: Vec2, radius: f32, color: Color, alpha: f32);
+    fn stroke_circle(&mut self, center: Vec2, radius: f32, stroke: &Stroke);
+    fn fill_arc(&mut self, center: Vec2, radius: f32, start: f32, end: f32, color: Color, alpha: f32);
+    fn stroke_arc(&mut self, center: Vec2, radius: f32, start: f32, end: f32, stroke: &Stroke);
+    fn fill_rounded_rect(&mut self, rect: &Rect, radius: f32, color: Color, alpha: f32);
+    fn stroke_rounded_rect(&mut self, rect: &Rect, radius: f32, stroke: &Stroke);
+}
+
+impl Shapes for Framebuffer {
+    // Línea de un píxel con el algoritmo de Xiaolin Wu
+    fn line_aa(&mut self, from: Vec2, to: Vec2, color: Color, alpha: f32) {
+        // Wu trabaja con los centros de píxel en coordenadas enteras
+        let (mut x0, mut y0) = (from.x - 0.5, from.y - 0.5);
+        let (mut x1, mut y1) = (to.x - 0.5, to.y - 0.5);
+
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        if steep {
+            std::mem::swap(&mut x0, &mut y0);
+            std::mem::swap(&mut x1, &mut y1);
+        }
+        if x0 > x1 {
+            std::mem::swap(&mut x0, &mut x1);
+            std::mem::swap(&mut y0, &mut y1);
+        }
+
+        let dx = x1 - x0;
+        let gradient = if dx == 0.0 { 1.0 } else { (y1 - y0) / dx };
+
+        let mut plot = |x: f32, y: f32, coverage: f32| {
+            let (px, py) = if steep { (y, x) } else { (x, y) };
+            self.blend_point(px as isize, py as isize, 0.0, color, coverage * alpha);
+        };
+
+        // Parte fraccionaria también correcta para coordenadas negativas
+        let fpart = |v: f32| v - v.floor();
+
+        // Extremos: se pondera por la parte del píxel que cubre la línea
+        let x_start = x0.round();
+        let y_start = y0 + gradient * (x_start - x0);
+        let gap_start = 1.0 - fpart(x0 + 0.5);
+        plot(x_start, y_start.floor(), (1.0 - fpart(y_start)) * gap_start);
+        plot(x_start, y_start.floor() + 1.0, fpart(y_start) * gap_start);
+
+        let x_end = x1.round();
+        let y_end = y1 + gradient * (x_end - x1);
+        let gap_end = fpart(x1 + 0.5);
+        plot(x_end, y_end.floor(), (1.0 - fpart(y_end)) * gap_end);
+        plot(x_end, y_end.floor() + 1.0, fpart(y_end) * gap_end);
+
+        let mut y = y_start + gradient;
+        let mut x = x_start + 1.0;
+        while x < x_end {
+            plot(x, y.floor(), 1.0 - fpart(y));
+            plot(x, y.floor() + 1.0, fpart(y));
+            y += gradient;
+            x += 1.0;
+        }
+    }
+
+    fn stroke_line(&mut self, from: Vec2, to: Vec2, stroke: &Stroke) {
+        let length = (to - from).magnitude();
+
+        // Cada trazo de la línea discontinua se dibuja como una línea con sus propios extremos
+        if let Some((dash, gap)) = stroke.dash {
+            if dash > 0.0 && length > 0.0 {
+                let direction = (to - from) / length;
+                let mut start = 0.0;
+                while start < length {
+                    let end = (start + dash).min(length);
+                    let solid = Stroke { dash: None, ..*stroke };
+                    self.stroke_line(from + direction * start, from + direction * end, &solid);
+                    start += dash + gap.max(0.0);
+                }
+                return;
+            }
+        }
+
+        let direction = if length > 0.0 { (to - from) / length } else { Vec2::new(1.0, 0.0) };
+        let half_width = stroke.width / 2.0;
+        let extension = if stroke.cap == LineCap::Square { half_width } else { 0.0 };
+
+        let margin = half_width + extension + 1.0;
+        let bounds = (from.x.min(to.x) - margin, from.y.min(to.y) - margin, from.x.max(to.x) + margin, from.y.max(to.y) + margin);
+
+        fill_sdf(self, bounds, stroke.color, stroke.alpha, |p| {
+            let local = p - from;
+            let along = local.dot(&direction);
+
+            match stroke.cap {
+                LineCap::Round => {
+                    let closest = from + direction * along.clamp(0.0, length);
+                    (p - closest).magnitude() - half_width
+                }
+                LineCap::Butt | LineCap::Square => {
+                    let across = local.x * direction.y - local.y * direction.x;
+                    box_distance(along - length / 2.0, across, length / 2.0 + extension, half_width)
+                }
+            }
+        });
+    }
+
+    fn fill_circle(&mut self, center: Vec2, radius: f32, color: Color, alpha: f32) {
+        self.fill_arc(center, radius, 0.0, TAU, color, alpha);
+    }
+
+    fn stroke_circle(&mut self, center: Vec2, radius: f32, stroke: &Stroke) {
+        self.stroke_arc(center, radius, 0.0, TAU, stroke);
+    }
+
+    // Sector circular entre los ángulos `start` y `end` (radianes, en sentido horario en pantalla)
+    fn fill_arc(&mut self, center: Vec2, radius: f32, start: f32, end: f32, color: Color, alpha: f32) {
+        let margin = radius + 1.0;
+        let bounds = (center.x - margin, center.y - margin, center.x + margin, center.y + margin);
+
+        fill_sdf(self, bounds, color, alpha, |p| {
+            let local = p - center;
+            f32::max(local.magnitude() - radius, wedge_distance(local, start, end))
+        });
+    }
+
+    // Arco de circunferencia; los extremos son siempre rectos y los trazos discontinuos siguen la curva
+    fn stroke_arc(&mut self, center: Vec2, radius: f32, start: f32, end: f32, stroke: &Stroke) {
+        let half_width = stroke.width / 2.0;
+        let margin = radius + half_width + 1.0;
+        let bounds = (center.x - margin, center.y - margin, center.x + margin, center.y + margin);
+
+        fill_sdf(self, bounds, stroke.color, stroke.alpha, |p| {
+            let local = p - center;
+            let ring = (local.magnitude() - radius).abs() - half_width;
+            let mut distance = f32::max(ring, wedge_distance(local, start, end));
+
+            if let Some((dash, gap)) = stroke.dash {
+                let angle = (local.y.atan2(local.x) - start).rem_euclid(TAU);
+                distance = distance.max(dash_distance(angle * radius, dash, gap));
+            }
+
+            distance
+        });
+    }
+
+    fn fill_rounded_rect(&mut self, rect: &Rect, radius: f32, color: Color, alpha: f32) {
+        let (center, half_size, radius) = rounded_rect_shape(rect, radius);
+        let bounds = (rect.x as f32, rect.y as f32, (rect.x + rect.width as isize) as f32, (rect.y + rect.height as isize) as f32);
+
+        fill_sdf(self, bounds, color, alpha, |p| rounded_box_distance(p - center, half_size, radius));
+    }
+
+    // Contorno centrado sobre el borde del rectángulo
+    fn stroke_rounded_rect(&mut self, rect: &Rect, radius: f32, stroke: &Stroke) {
+        let (center, half_size, radius) = rounded_rect_shape(rect, radius);
+        let margin = stroke.width / 2.0 + 1.0;
+        let bounds = (
+            rect.x as f32 - margin,
+            rect.y as f32 - margin,
+            (rect.x + rect.width as isize) as f32 + margin,
+            (rect.y + rect.height as isize) as f32 + margin,
+        );
+
+        fill_sdf(self, bounds, stroke.color, stroke.alpha, |p| {
+            rounded_box_distance(p - center, half_size, radius).abs() - stroke.width / 2.0
+        });
+    }
+}
+
+// Rellena los píxeles de `bounds` (min_x, min_y, max_x, max_y) según una función de distancia con signo
+// (negativa dentro de la figura). La cobertura del borde se aproxima con la distancia al centro del píxel.
+fn fill_sdf<F: Fn(Vec2) -> f32>(framebuffer: &mut Framebuffer, bounds: (f32, f32, f32, f32), color: Color, alpha: f32, distance: F) {
+    let min_x = bounds.0.floor().max(0.0) as usize;
+    let min_y = bounds.1.floor().max(0.0) as usize;
+    let max_x = bounds.2.ceil().min(framebuffer.width as f32).max(0.0) as usize;
+    let max_y = bounds.3.ceil().min(framebuffer.height as f32).max(0.0) as usize;
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let coverage = (0.5 - distance(Vec2::new(x as f32 + 0.5, y as f32 + 0.5))).clamp(0.0, 1.0);
+            if coverage > 0.0 {
+                framebuffer.blend_point(x as isize, y as isize, 0.0, color, coverage * alpha);
+            }
+        }
+    }
+}
+
+// Distancia a una caja centrada en el origen con semiejes (half_x, half_y)
+fn box_distance(x: f32, y: f32, half_x: f32, half_y: f32) -> f32 {
+    let qx = x.abs() - half_x;
+    let qy = y.abs() - half_y;
+    Vec2::new(qx.max(0.0), qy.max(0.0)).magnitude() + qx.max(qy).min(0.0)
+}
+
+fn rounded_box_distance(p: Vec2, half_size: Vec2, radius: f32) -> f32 {
+    box_distance(p.x, p.y, half_size.x - radius, half_size.y - radius) - radius
+}
+
+// Centro, semiejes y radio (limitado a la mitad del lado menor) de un rectángulo redondeado
+fn rounded_rect_shape(rect: &Rect, radius: f32) -> (Vec2, Vec2, f32) {
+    let half_size = Vec2::new(rect.width as f32 / 2.0, rect.height as f32 / 2.0);
+    let center = Vec2::new(rect.x as f32, rect.y as f32) + half_size;
+    (center, half_size, radius.clamp(0.0, half_size.x.min(half_size.y)))
+}
+
+// Distancia con signo a la cuña que va de `start` a `end` en sentido creciente, con vértice en el origen.
+// Si `end` es menor que `start` la cuña cruza el ángulo 0, como `start` → `end + TAU`.
+fn wedge_distance(p: Vec2, start: f32, end: f32) -> f32 {
+    if (end - start).abs() >= TAU {
+        return f32::NEG_INFINITY;
+    }
+    let sweep = (end - start).rem_euclid(TAU);
+
+    // Se gira el punto para que la bisectriz quede sobre +x; la cuña es simétrica respecto a ella
+    let half = sweep / 2.0;
+    let (sin, cos) = (start + half).sin_cos();
+    let x = p.x * cos + p.y * sin;
+    let y = (-p.x * sin + p.y * cos).abs();
+
+    let (edge_sin, edge_cos) = half.sin_cos();
+    let along_edge = x * edge_cos + y * edge_sin;
+    let to_edge = if along_edge > 0.0 { (x * edge_sin - y * edge_cos).abs() } else { p.magnitude() };
+
+    if y.atan2(x) <= half {
+        -to_edge
+    } else {
+        to_edge
+    }
+}
+
+// Distancia con signo al trazo más cercano de un patrón discontinuo, medida a lo largo del recorrido
+fn dash_distance(position: f32, dash: f32, gap: f32) -> f32 {
+    let period = dash + gap.max(0.0);
+    if period <= 0.0 {
+        return f32::NEG_INFINITY;
+    }
+
+    let offset = position.rem_euclid(period);
+    if offset <= dash {
+        -offset.min(dash - offset)
+    } else {
+        (offset - dash).min(period - offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    const SIZE: usize = 16;
+
+    // Capa overlay vacía: el byte alto de cada píxel es la cobertura por el alfa del dibujo
+    fn canvas() -> Framebuffer {
+        Framebuffer::new_overlay(SIZE, SIZE)
+    }
+
+    fn alpha(framebuffer: &Framebuffer, x: usize, y: usize) -> u32 {
+        framebuffer.buffer[y * SIZE + x] >> 24
+    }
+
+    fn total_alpha(framebuffer: &Framebuffer) -> f32 {
+        framebuffer.buffer.iter().map(|pixel| (pixel >> 24) as f32 / 255.0).sum()
+    }
+
+    fn white() -> Color {
+        Color::new(255, 255, 255)
+    }
+
+    fn stroke(width: f32, cap: LineCap) -> Stroke {
+        Stroke { color: white(), width, cap, ..Stroke::default() }
+    }
+
+    #[test]
+    fn wu_line_splits_each_column_between_two_pixels() {
+        let mut framebuffer = canvas();
+        framebuffer.line_aa(Vec2::new(1.5, 1.5), Vec2::new(13.5, 6.5), white(), 1.0);
+
+        for x in 2..13 {
+            let column: u32 = (0..SIZE).map(|y| alpha(&framebuffer, x, y)).sum();
+            assert!((254..=256).contains(&column), "column {} sums to {}", x, column);
+        }
+        // Sobre los centros de píxel una línea horizontal cubre una sola fila
+        let mut framebuffer = canvas();
+        framebuffer.line_aa(Vec2::new(2.5, 8.5), Vec2::new(12.5, 8.5), white(), 1.0);
+        assert_eq!(alpha(&framebuffer, 6, 8), 255);
+        assert_eq!(alpha(&framebuffer, 6, 7), 0);
+        assert_eq!(alpha(&framebuffer, 6, 9), 0);
+    }
+
+    #[test]
+    fn thick_line_caps_extend_past_the_endpoints() {
+        let from = Vec2::new(4.0, 8.0);
+        let to = Vec2::new(12.0, 8.0);
+
+        let mut butt = canvas();
+        butt.stroke_line(from, to, &stroke(4.0, LineCap::Butt));
+        let mut square = canvas();
+        square.stroke_line(from, to, &stroke(4.0, LineCap::Square));
+        let mut round = canvas();
+        round.stroke_line(from, to, &stroke(4.0, LineCap::Round));
+
+        // Todas cubren el cuerpo de la línea con su ancho
+        for framebuffer in [&butt, &square, &round] {
+            assert_eq!(alpha(framebuffer, 8, 6), 255);
+            assert_eq!(alpha(framebuffer, 8, 9), 255);
+            assert_eq!(alpha(framebuffer, 8, 10), 0);
+        }
+
+        // Más allá del extremo: nada, medio ancho completo, o el semicírculo
+        assert_eq!(alpha(&butt, 3, 7), 0);
+        assert_eq!(alpha(&square, 2, 6), 255);
+        assert_eq!(alpha(&round, 3, 7), 255);
+        assert!(alpha(&round, 2, 6) > 0 && alpha(&round, 2, 6) < 255);
+    }
+
+    #[test]
+    fn dashed_line_alternates_dashes_and_gaps() {
+        let mut framebuffer = canvas();
+        let dashed = Stroke { dash: Some((2.0, 2.0)), ..stroke(2.0, LineCap::Butt) };
+        framebuffer.stroke_line(Vec2::new(0.0, 8.0), Vec2::new(16.0, 8.0), &dashed);
+
+        let row: Vec<bool> = (0..SIZE).map(|x| alpha(&framebuffer, x, 7) == 255).collect();
+        assert_eq!(&row[..8], &[true, true, false, false, true, true, false, false]);
+    }
+
+    #[test]
+    fn circles_cover_their_area_with_soft_edges() {
+        let mut filled = canvas();
+        filled.fill_circle(Vec2::new(8.0, 8.0), 5.0, white(), 1.0);
+
+        assert_eq!(alpha(&filled, 8, 8), 255);
+        assert_eq!(alpha(&filled, 0, 0), 0);
+        assert!((total_alpha(&filled) - PI * 25.0).abs() < 0.5);
+        // El borde queda con cobertura parcial
+        assert!(alpha(&filled, 12, 5) > 0 && alpha(&filled, 12, 5) < 255);
+
+        let mut outlined = canvas();
+        outlined.stroke_circle(Vec2::new(8.0, 8.0), 5.0, &stroke(2.0, LineCap::Butt));
+        assert_eq!(alpha(&outlined, 8, 8), 0);
+        assert_eq!(alpha(&outlined, 8, 3), 255);
+        assert_eq!(alpha(&outlined, 8, 3), 255);
+    }
+
+    #[test]
+    fn arcs_cover_only_their_sweep() {
+        // Ángulos en sentido horario en pantalla: de +x hacia abajo
+        let mut quarter = canvas();
+        quarter.fill_arc(Vec2::new(8.0, 8.0), 6.0, 0.0, FRAC_PI_2, white(), 1.0);
+        assert_eq!(alpha(&quarter, 10, 10), 255);
+        assert_eq!(alpha(&quarter, 5, 5), 0);
+        assert_eq!(alpha(&quarter, 10, 5), 0);
+        assert!((total_alpha(&quarter) - PI * 36.0 / 4.0).abs() < 0.5);
+
+        // Con el final menor que el inicio la cuña cruza el ángulo 0: mitad derecha
+        let mut wrapped = canvas();
+        wrapped.fill_arc(Vec2::new(8.0, 8.0), 6.0, 3.0 * FRAC_PI_2, FRAC_PI_2, white(), 1.0);
+        assert_eq!(alpha(&wrapped, 11, 7), 255);
+        assert_eq!(alpha(&wrapped, 11, 8), 255);
+        assert_eq!(alpha(&wrapped, 4, 8), 0);
+
+        let mut outlined = canvas();
+        outlined.stroke_arc(Vec2::new(8.0, 8.0), 5.0, PI, 3.0 * FRAC_PI_2, &stroke(2.0, LineCap::Butt));
+        assert_eq!(alpha(&outlined, 4, 4), 255);
+        assert_eq!(alpha(&outlined, 11, 11), 0);
+        assert_eq!(alpha(&outlined, 8, 8), 0);
+    }
+
+    #[test]
+    fn rounded_rect_cuts_its_corners() {
+        let rect = Rect { x: 2, y: 2, width: 12, height: 12 };
+
+        let mut filled = canvas();
+        filled.fill_rounded_rect(&rect, 4.0, white(), 1.0);
+        assert_eq!(alpha(&filled, 8, 8), 255);
+        assert_eq!(alpha(&filled, 8, 2), 255);
+        assert_eq!(alpha(&filled, 2, 2), 0);
+        assert_eq!(alpha(&filled, 1, 8), 0);
+
+        let mut outlined = canvas();
+        outlined.stroke_rounded_rect(&rect, 4.0, &stroke(2.0, LineCap::Butt));
+        assert_eq!(alpha(&outlined, 8, 8), 0);
+        assert_eq!(alpha(&outlined, 8, 1), 255);
+        assert_eq!(alpha(&outlined, 8, 2), 255);
+        assert_eq!(alpha(&outlined, 8, 3), 0);
+    }
+
+    #[test]
+    fn alpha_scales_the_coverage() {
+        let mut framebuffer = canvas();
+        framebuffer.fill_circle(Vec2::new(8.0, 8.0), 5.0, white(), 0.5);
+        assert_eq!(alpha(&framebuffer, 8, 8), 128);
+
+        let mut framebuffer = canvas();
+        let faint = Stroke { alpha: 0.25, ..stroke(2.0, LineCap::Butt) };
+        framebuffer.stroke_line(Vec2::new(2.0, 8.0), Vec2::new(14.0, 8.0), &faint);
+        assert_eq!(alpha(&framebuffer, 8, 8), 64);
+    }
+
+    #[test]
+    fn scissor_clips_every_primitive() {
+        let mut framebuffer = canvas();
+        framebuffer.set_scissor(Some(Rect { x: 0, y: 0, width: 8, height: SIZE }));
+
+        framebuffer.fill_circle(Vec2::new(8.0, 4.0), 3.0, white(), 1.0);
+        framebuffer.line_aa(Vec2::new(2.5, 8.5), Vec2::new(13.5, 8.5), white(), 1.0);
+        framebuffer.stroke_line(Vec2::new(2.0, 12.0), Vec2::new(14.0, 12.0), &stroke(2.0, LineCap::Round));
+
+        for y in 0..SIZE {
+            for x in 8..SIZE {
+                assert_eq!(alpha(&framebuffer, x, y), 0, "pixel ({}, {}) outside the scissor", x, y);
+            }
+        }
+        assert_eq!(alpha(&framebuffer, 6, 4), 255);
+        assert_eq!(alpha(&framebuffer, 6, 8), 255);
+        assert_eq!(alpha(&framebuffer, 6, 12), 255);
+    }
+}
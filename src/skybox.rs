@@ -1,104 +1,283 @@
-use nalgebra_glm::{Vec3, Vec4, normalize};
+use nalgebra_glm::{Vec3, normalize};
 use rand::prelude::*;
+use rand::rngs::StdRng;
 use std::f32::consts::PI;
-use crate::{Framebuffer, Uniforms, Color};
+use crate::{Framebuffer, Color};
+use crate::camera::Camera;
+use crate::nebula::{ProceduralSky, random_direction};
+use crate::rect::Rect;
+use crate::texture::{CubeMap, Texture};
+use image::ImageResult;
+use std::path::Path;
+
+// Tamaño en píxeles del bloque con el que se muestrea el fondo
+const BACKGROUND_BLOCK: usize = 2;
+
+// Escala de píxeles de la ventana original de 800 px de alto con 45° de FOV
+const REFERENCE_PIXEL_SCALE: f32 = 965.69;
 
 pub struct Star {
-    position: Vec3,
+    direction: Vec3,
+    distance: f32, // Radio de la capa; `f32::INFINITY` no tiene paralaje
     brightness: f32,
     size: u8,
+    color: Color,
+    twinkle_phase: f32,
+}
+
+impl Star {
+    pub fn new(direction: Vec3, brightness: f32, size: u8, color: Color) -> Self {
+        let direction = normalize(&direction);
+
+        // Fase de centelleo derivada de la dirección para que sea estable entre ejecuciones
+        let hash = (direction.dot(&Vec3::new(12.9898, 78.233, 37.719)).sin() * 43758.547).fract();
+
+        Star {
+            direction,
+            distance: f32::INFINITY,
+            brightness,
+            size,
+            color,
+            twinkle_phase: hash.abs() * 2.0 * PI,
+        }
+    }
+}
+
+// Capa esférica de estrellas a una distancia dada del origen del sistema
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StarLayer {
+    pub count: usize,
+    pub distance: f32,
+}
+
+impl StarLayer {
+    // Capas por defecto: dos capas cercanas con paralaje sutil y una en el infinito
+    pub fn defaults() -> Vec<StarLayer> {
+        vec![
+            StarLayer { count: 6000, distance: 1.0e6 },
+            StarLayer { count: 3000, distance: 4.0e6 },
+            StarLayer { count: 1000, distance: f32::INFINITY },
+        ]
+    }
 }
 
 pub struct Skybox {
     stars: Vec<Star>,
+    background: Option<Background>,
+    twinkle: f32, // Amplitud del centelleo en [0, 1]; 0 lo desactiva
+}
+
+// Capa de fondo que se dibuja por píxel detrás de las estrellas
+pub enum Background {
+    Procedural(Box<ProceduralSky>),
+    CubeMap(CubeMap),
+    Panorama(Texture), // Proyección equirectangular
+}
+
+impl Background {
+    pub fn sample(&self, direction: &Vec3) -> Color {
+        match self {
+            Background::Procedural(sky) => sky.sample(direction),
+            Background::CubeMap(cube_map) => cube_map.sample(direction),
+            Background::Panorama(texture) => texture.sample_direction(direction),
+        }
+    }
 }
 
 impl Skybox {
-    /// Crea un nuevo Skybox con una cantidad de estrellas uniformemente distribuidas y propiedades como brillo y tamaño.
-    pub fn new(star_count: usize) -> Self {
-        let mut rng = thread_rng();
-        let mut stars = Vec::with_capacity(star_count);
+    /// Crea un Skybox determinista a partir de una semilla, con una capa de estrellas por cada `StarLayer`.
+    pub fn seeded(seed: u64, layers: &[StarLayer]) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let stars = generate_stars(&mut rng, layers, |_| 1.0);
 
-        for _ in 0..star_count {
-            // Distribución uniforme en la esfera.
-            let theta = rng.gen_range(0.0..2.0 * PI); // Ángulo azimutal
-            let phi = rng.gen_range(0.0..PI);         // Ángulo polar
+        Skybox { stars, background: None, twinkle: 0.0 }
+    }
 
-            let x = phi.sin() * theta.cos();
-            let y = phi.sin() * theta.sin();
-            let z = phi.cos();
+    /// Crea un cielo procedural determinista: la densidad de estrellas sigue la banda galáctica
+    /// y, si se indica `cube_size`, el fondo se hornea en un cube map para que sea barato en tiempo real.
+    pub fn procedural(layers: &[StarLayer], sky: ProceduralSky, cube_size: Option<usize>) -> Self {
+        let mut rng = StdRng::seed_from_u64(sky.seed);
+        let stars = generate_stars(&mut rng, layers, |direction| sky.star_density(direction));
 
-            let position = normalize(&Vec3::new(x, y, z)) * 100.0; // Escala para simular un radio
-            let brightness = rng.gen_range(0.5..1.0);              // Brillo entre 0.5 y 1.0
-            let size: u8 = rng.gen_range(1..=3);                   // Tamaño aleatorio
+        let background = match cube_size {
+            Some(size) => Background::CubeMap(CubeMap::from_fn(size, |direction| sky.sample(direction))),
+            None => Background::Procedural(Box::new(sky)),
+        };
 
-            stars.push(Star { position, brightness, size });
-        }
+        Skybox { stars, background: Some(background), twinkle: 0.0 }
+    }
 
-        Skybox { stars }
+    /// Activa el centelleo de las estrellas con la amplitud dada (0 lo desactiva).
+    pub fn with_twinkle(mut self, amount: f32) -> Self {
+        self.twinkle = amount.clamp(0.0, 1.0);
+        self
     }
 
-    /// Renderiza las estrellas en el framebuffer teniendo en cuenta la posición de la cámara y la profundidad Z.
-pub fn render(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms, camera_position: Vec3) {
-    for star in &self.stars {
-        // Calcular la posición de la estrella relativa a la cámara.
-        let position = star.position + camera_position;
+    /// Crea un skybox sin fondo con las estrellas dadas, por ejemplo las de un catálogo real.
+    pub fn from_stars(stars: Vec<Star>) -> Self {
+        Skybox { stars, background: None, twinkle: 0.0 }
+    }
 
-        // Convertir la posición a un vec4 para las transformaciones.
-        let pos_vec4 = Vec4::new(position.x, position.y, position.z, 1.0);
+    /// Crea un skybox a partir de imágenes: un directorio con las seis caras de un cube map
+    /// (`px`, `nx`, `py`, `ny`, `pz`, `nz`) o un único archivo con un panorama equirectangular.
+    pub fn from_image(path: &str) -> ImageResult<Self> {
+        let background = if Path::new(path).is_dir() {
+            Background::CubeMap(CubeMap::load_dir(path)?)
+        } else {
+            Background::Panorama(Texture::load(path)?)
+        };
 
-        // Aplicar la matriz de vista y proyección.
-        let projected = uniforms.projection_matrix * uniforms.view_matrix * pos_vec4;
+        Ok(Skybox { stars: Vec::new(), background: Some(background), twinkle: 0.0 })
+    }
 
-        // Verificar si la estrella está detrás de la cámara.
-        if projected.w <= 0.0 {
-            continue;
-        }
+    /// Dibuja el fondo por píxel a partir de la dirección de vista de cada píxel, sin usar el z-buffer.
+    fn render_background(&self, framebuffer: &mut Framebuffer, area: &Rect, camera: &Camera, field_of_view: f32) {
+        let Some(background) = &self.background else {
+            return;
+        };
 
-        // Normalizar a coordenadas NDC.
-        let ndc = projected / projected.w;
-
-        let screen_pos = uniforms.view_matrix * Vec4::new(ndc.x,ndc.y,ndc.z,1.0);
-
-        // Convertir las coordenadas NDC a coordenadas de pantalla.
-        let screen_x = -screen_pos.x as i32 + ((ndc.x + 1.0) * 0.5 * framebuffer.width as f32) as i32;
-        let screen_y = screen_pos.y as i32 + ((1.0 - ndc.y) * 0.5 * framebuffer.height as f32) as i32;
-
-        // Verificar si la estrella está dentro de la pantalla.
-        if screen_x >= 0
-            && screen_y >= 0
-            && screen_x < framebuffer.width as i32
-            && screen_y < framebuffer.height as i32
-        {
-            // Calcular intensidad del color basado en el brillo de la estrella.
-            let intensity = (star.brightness * 255.0) as u8;
-            let color = Color::new(intensity as i32, intensity as i32, intensity as i32);
-
-            // Establecer el color actual en el framebuffer.
-            framebuffer.set_current_color(color);
-
-            // Dibujar la estrella según su tamaño.
-            match star.size {
-                1 => framebuffer.point(screen_x as isize, screen_y as isize, 1000.0),
-                2 => {
-                    framebuffer.point(screen_x as isize, screen_y as isize, 1000.0);
-                    framebuffer.point((screen_x + 1) as isize, screen_y as isize, 1000.0);
-                    framebuffer.point(screen_x as isize, (screen_y + 1) as isize, 1000.0);
-                    framebuffer.point((screen_x + 1) as isize, (screen_y + 1) as isize, 1000.0);
-                }
-                3 => {
-                    framebuffer.point(screen_x as isize, screen_y as isize, 1000.0);
-                    framebuffer.point((screen_x - 1) as isize, screen_y as isize, 1000.0);
-                    framebuffer.point((screen_x + 1) as isize, screen_y as isize, 1000.0);
-                    framebuffer.point(screen_x as isize, (screen_y - 1) as isize, 1000.0);
-                    framebuffer.point(screen_x as isize, (screen_y + 1) as isize, 1000.0);
+        let width = area.width as f32;
+        let height = area.height as f32;
+        let tan_half_fov = (field_of_view / 2.0).tan();
+        let aspect_ratio = width / height;
+
+        // `basis_change` es lineal, así que basta con transformar los ejes una vez
+        let right = camera.basis_change(&Vec3::new(tan_half_fov * aspect_ratio, 0.0, 0.0));
+        let up = camera.basis_change(&Vec3::new(0.0, tan_half_fov, 0.0));
+        let forward = camera.basis_change(&Vec3::new(0.0, 0.0, -1.0));
+
+        // El fondo es de baja frecuencia: se muestrea una vez por bloque de 2x2 píxeles
+        let block = BACKGROUND_BLOCK;
+        let (left, top) = (area.x as usize, area.y as usize);
+        for by in (0..area.height).step_by(block) {
+            let ndc_y = 1.0 - 2.0 * (by as f32 + block as f32 / 2.0) / height;
+            for bx in (0..area.width).step_by(block) {
+                let ndc_x = 2.0 * (bx as f32 + block as f32 / 2.0) / width - 1.0;
+
+                // Rayo en espacio de cámara (mirando hacia -z) llevado al espacio del mundo
+                let direction = right * ndc_x + up * ndc_y + forward;
+                let color = background.sample(&direction).to_hex();
+
+                for y in top + by..top + (by + block).min(area.height) {
+                    let row = y * framebuffer.width;
+                    for x in left + bx..left + (bx + block).min(area.width) {
+                        framebuffer.buffer[row + x] = color;
+                    }
                 }
-                _ => {}
             }
         }
     }
+
+    /// Dibuja el fondo y las estrellas antes de la escena. Sólo se usa la rotación de la cámara
+    /// (más un paralaje sutil en las capas cercanas) y no se toca el z-buffer, así que cualquier
+    /// geometría dibujada después queda por encima. `time` es el mismo contador que `Uniforms::time`.
+    ///
+    /// Se dibuja dentro del recorte del framebuffer (o en todo él), con `field_of_view` vertical
+    /// igual al de la proyección de la escena en esa zona.
+    pub fn render(&self, framebuffer: &mut Framebuffer, camera: &Camera, field_of_view: f32, time: u32) {
+        let area = framebuffer.clip_rect();
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        self.render_background(framebuffer, &area, camera, field_of_view);
+
+        let width = area.width as f32;
+        let height = area.height as f32;
+        let tan_half_fov = (field_of_view / 2.0).tan();
+        let aspect_ratio = width / height;
+        let (right, up, forward) = camera.basis();
+
+        // Píxeles por unidad de plano de imagen, relativo a la ventana original (800 px a 45°)
+        let pixel_scale = (height / (2.0 * tan_half_fov)) / REFERENCE_PIXEL_SCALE;
+
+        for star in &self.stars {
+            // Las capas a distancia finita se desplazan levemente cuando la nave se mueve
+            let direction = if star.distance.is_finite() {
+                (star.direction * star.distance - camera.eye).normalize()
+            } else {
+                star.direction
+            };
+
+            // Dirección en espacio de cámara
+            let depth = direction.dot(&forward);
+            if depth <= 0.0 {
+                continue;
+            }
+
+            let ndc_x = direction.dot(&right) / (depth * tan_half_fov * aspect_ratio);
+            let ndc_y = direction.dot(&up) / (depth * tan_half_fov);
+
+            let screen_x = area.x as f32 + (ndc_x + 1.0) * 0.5 * width;
+            let screen_y = area.y as f32 + (1.0 - ndc_y) * 0.5 * height;
+
+            // `size` es el diámetro en píxeles a la resolución y FOV de referencia
+            let radius = star.size as f32 * 0.5 * pixel_scale;
+            let twinkle = 1.0 - self.twinkle * (0.5 + 0.5 * (time as f32 * 0.15 + star.twinkle_phase).sin());
+            draw_star(framebuffer, &area, screen_x, screen_y, radius, star.color * (star.brightness * twinkle));
+        }
+    }
 }
 
+// Genera las estrellas de cada capa; `density` en [0, 1] permite concentrarlas por muestreo por rechazo
+fn generate_stars<R: Rng, F: Fn(&Vec3) -> f32>(rng: &mut R, layers: &[StarLayer], density: F) -> Vec<Star> {
+    let mut stars = Vec::with_capacity(layers.iter().map(|layer| layer.count).sum());
+
+    for layer in layers {
+        let mut generated = 0;
+        while generated < layer.count {
+            let direction = random_direction(rng);
+            if rng.gen::<f32>() > density(&direction) {
+                continue;
+            }
+
+            let brightness = rng.gen_range(0.4..1.0);
+            let size: u8 = if rng.gen::<f32>() < 0.85 { 1 } else { rng.gen_range(2..=3) };
+
+            stars.push(Star {
+                direction,
+                distance: layer.distance,
+                brightness,
+                size,
+                color: Color::new(255, 255, 255),
+                twinkle_phase: rng.gen_range(0.0..2.0 * PI),
+            });
+            generated += 1;
+        }
+    }
+
+    stars
 }
 
+// Dibuja un disco suavizado sumando su color sobre el fondo, sin prueba de profundidad.
+// Las estrellas más pequeñas que un píxel conservan su energía bajando la intensidad.
+fn draw_star(framebuffer: &mut Framebuffer, area: &Rect, x: f32, y: f32, radius: f32, color: Color) {
+    let (radius, color) = if radius < 0.5 {
+        (0.5, color * (2.0 * radius).powi(2))
+    } else {
+        (radius, color)
+    };
+
+    let min_x = (x - radius).floor() as isize;
+    let max_x = (x + radius).ceil() as isize;
+    let min_y = (y - radius).floor() as isize;
+    let max_y = (y + radius).ceil() as isize;
 
+    for py in min_y..=max_y {
+        for px in min_x..=max_x {
+            if !area.contains(px, py) {
+                continue;
+            }
+
+            let dx = px as f32 + 0.5 - x;
+            let dy = py as f32 + 0.5 - y;
+            let coverage = (radius + 0.5 - (dx * dx + dy * dy).sqrt()).clamp(0.0, 1.0);
+            if coverage <= 0.0 {
+                continue;
+            }
+
+            let index = py as usize * framebuffer.width + px as usize;
+            let background = Color::from_hex(framebuffer.buffer[index]);
+            framebuffer.buffer[index] = (background + color * coverage).to_hex();
+        }
+    }
+}
@@ -0,0 +1,140 @@
+// sprite.rs
+
+use crate::color::Color;
+use crate::rect::Rect;
+use crate::texture::Texture;
+
+// Límite de muestras por eje al reducir, para que una imagen muy reducida no cueste de más
+const MAX_TAPS: usize = 8;
+
+// Parámetros para dibujar una textura (o un cuadro de una hoja de sprites) en pantalla.
+// `x`, `y` es la posición en píxeles del punto de pivote `origin`, dado en fracciones del tamaño del sprite.
+#[derive(Debug, Clone, Copy)]
+pub struct Sprite {
+    pub x: f32,
+    pub y: f32,
+    pub scale_x: f32, // Negativo refleja el sprite
+    pub scale_y: f32,
+    pub rotation: f32, // Radianes, en sentido horario en pantalla
+    pub alpha: f32,
+    pub tint: Color, // Se multiplica por el color de cada texel
+    pub source: Option<Rect>, // Subrectángulo de la textura; `None` usa la textura completa
+    pub origin: (f32, f32),
+    pub smooth: bool, // Bilineal o vecino más cercano (pixel art)
+}
+
+impl Default for Sprite {
+    fn default() -> Self {
+        Sprite {
+            x: 0.0,
+            y: 0.0,
+            scale_x: 1.0,
+            scale_y: 1.0,
+            rotation: 0.0,
+            alpha: 1.0,
+            tint: Color::new(255, 255, 255),
+            source: None,
+            origin: (0.0, 0.0),
+            smooth: true,
+        }
+    }
+}
+
+impl Sprite {
+    // Rectángulo de la textura que se dibuja, recortado a sus bordes
+    pub fn source_rect(&self, texture: &Texture) -> Rect {
+        let full = Rect { x: 0, y: 0, width: texture.width, height: texture.height };
+        let Some(source) = self.source else {
+            return full;
+        };
+
+        let x = source.x.clamp(0, texture.width as isize);
+        let y = source.y.clamp(0, texture.height as isize);
+        let right = (source.x + source.width as isize).clamp(x, texture.width as isize);
+        let bottom = (source.y + source.height as isize).clamp(y, texture.height as isize);
+
+        Rect { x, y, width: (right - x) as usize, height: (bottom - y) as usize }
+    }
+}
+
+// Color y alfa del texel en (u, v), en píxeles relativos a `source`, sin salir del subrectángulo
+pub fn sample(texture: &Texture, source: &Rect, u: f32, v: f32, smooth: bool) -> (Color, f32) {
+    let texel = |x: isize, y: isize| {
+        let x = (source.x + x.clamp(0, source.width as isize - 1)) as usize;
+        let y = (source.y + y.clamp(0, source.height as isize - 1)) as usize;
+        texture.pixels[y * texture.width + x]
+    };
+    let alpha_of = |pixel: u32| (pixel >> 24) as f32 / 255.0;
+
+    if !smooth {
+        let pixel = texel(u.floor() as isize, v.floor() as isize);
+        return (Color::from_hex(pixel), alpha_of(pixel));
+    }
+
+    let x = u - 0.5;
+    let y = v - 0.5;
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (x0, y0) = (x0 as isize, y0 as isize);
+
+    // Interpolación con alfa premultiplicado para que los bordes transparentes no oscurezcan el color
+    let mut rgb = [0.0f32; 3];
+    let mut alpha = 0.0;
+    for (dx, dy, weight) in [(0, 0, (1.0 - fx) * (1.0 - fy)), (1, 0, fx * (1.0 - fy)), (0, 1, (1.0 - fx) * fy), (1, 1, fx * fy)] {
+        let pixel = texel(x0 + dx, y0 + dy);
+        let a = alpha_of(pixel) * weight;
+        rgb[0] += ((pixel >> 16) & 0xFF) as f32 * a;
+        rgb[1] += ((pixel >> 8) & 0xFF) as f32 * a;
+        rgb[2] += (pixel & 0xFF) as f32 * a;
+        alpha += a;
+    }
+
+    if alpha <= 0.0 {
+        return (Color::new(0, 0, 0), 0.0);
+    }
+
+    let color = Color::new(
+        (rgb[0] / alpha).round() as i32,
+        (rgb[1] / alpha).round() as i32,
+        (rgb[2] / alpha).round() as i32,
+    );
+    (color, alpha)
+}
+
+// Como `sample`, pero promediando el área de `footprint_u`×`footprint_v` texeles que cubre un píxel de pantalla.
+// Al reducir una imagen cada píxel abarca varios texeles; con una sola muestra bilineal la imagen se ve serrada.
+pub fn sample_area(texture: &Texture, source: &Rect, u: f32, v: f32, footprint_u: f32, footprint_v: f32, smooth: bool) -> (Color, f32) {
+    if !smooth || (footprint_u <= 1.0 && footprint_v <= 1.0) {
+        return sample(texture, source, u, v, smooth);
+    }
+
+    // Muestras bilineales repartidas en el área, a lo sumo una por texel en cada eje
+    let taps_u = (footprint_u.ceil() as usize).clamp(1, MAX_TAPS);
+    let taps_v = (footprint_v.ceil() as usize).clamp(1, MAX_TAPS);
+    let mut rgb = [0.0f32; 3];
+    let mut alpha = 0.0;
+
+    for j in 0..taps_v {
+        for i in 0..taps_u {
+            let tap_u = u + ((i as f32 + 0.5) / taps_u as f32 - 0.5) * footprint_u;
+            let tap_v = v + ((j as f32 + 0.5) / taps_v as f32 - 0.5) * footprint_v;
+            let (color, a) = sample(texture, source, tap_u, tap_v, true);
+            let pixel = color.to_hex();
+            rgb[0] += ((pixel >> 16) & 0xFF) as f32 * a;
+            rgb[1] += ((pixel >> 8) & 0xFF) as f32 * a;
+            rgb[2] += (pixel & 0xFF) as f32 * a;
+            alpha += a;
+        }
+    }
+
+    if alpha <= 0.0 {
+        return (Color::new(0, 0, 0), 0.0);
+    }
+
+    let color = Color::new(
+        (rgb[0] / alpha).round() as i32,
+        (rgb[1] / alpha).round() as i32,
+        (rgb[2] / alpha).round() as i32,
+    );
+    (color, alpha / (taps_u * taps_v) as f32)
+}
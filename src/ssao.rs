@@ -0,0 +1,322 @@
+// ssao.rs
+
+use crate::rect::Rect;
+use crate::render::{render_depth, Uniforms};
+use crate::vertex::Vertex;
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+use std::f32::consts::TAU;
+
+const KERNEL_SIZE: usize = 16;
+const BLUR_RADIUS: isize = 4;
+
+// Oclusión ambiental en espacio de pantalla a partir de un z-buffer: reconstruye posición y normal de
+// cada píxel, cuenta cuántas muestras de una semiesfera quedan tapadas y suaviza el resultado sin
+// cruzar bordes de profundidad. No necesita ventana: `compute` trabaja solo con buffers.
+pub struct AmbientOcclusion {
+    radius: f32,    // Radio de la semiesfera en unidades de la escena
+    intensity: f32, // 0 = sin efecto, 1 = oclusión completa
+    kernel: Vec<Vec3>,
+    width: usize,
+    height: usize,
+    area: Rect, // Zona calculada; fuera de ella el factor es 1
+    depth: Vec<f32>,
+    positions: Vec<Option<Vec3>>, // Posición en espacio de cámara; `None` donde no hay geometría
+    occlusion: Vec<f32>,          // Factor para el término ambiental: 1 = sin ocluir
+    scratch: Vec<f32>,
+}
+
+impl AmbientOcclusion {
+    pub fn new(radius: f32, intensity: f32) -> Self {
+        // Muestras fijas dentro de la semiesfera +z, más densas cerca del centro
+        let kernel = (0..KERNEL_SIZE)
+            .map(|i| {
+                let direction = Vec3::new(
+                    hash(i as u32, 0) * 2.0 - 1.0,
+                    hash(i as u32, 1) * 2.0 - 1.0,
+                    hash(i as u32, 2).max(0.1),
+                )
+                .normalize();
+                let t = i as f32 / KERNEL_SIZE as f32;
+                direction * hash(i as u32, 3).max(0.1) * (0.1 + 0.9 * t * t)
+            })
+            .collect();
+
+        AmbientOcclusion {
+            radius,
+            intensity,
+            kernel,
+            width: 0,
+            height: 0,
+            area: Rect { x: 0, y: 0, width: 0, height: 0 },
+            depth: Vec::new(),
+            positions: Vec::new(),
+            occlusion: Vec::new(),
+            scratch: Vec::new(),
+        }
+    }
+
+    // Pasada previa de solo profundidad del modelo y cálculo de la oclusión para esas matrices.
+    // `area` es la vista del framebuffer de `width`×`height` donde el viewport de `uniforms` dibuja.
+    pub fn render(&mut self, uniforms: &Uniforms, vertex_array: &[Vertex], width: usize, height: usize, area: &Rect) {
+        let mut depth = std::mem::take(&mut self.depth);
+        depth.clear();
+        depth.resize(width * height, f32::INFINITY);
+
+        render_depth(&mut depth, width, height, uniforms, vertex_array);
+        self.compute(&depth, width, height, area, &uniforms.projection_matrix);
+        self.depth = depth;
+    }
+
+    // `depth` en el formato del z-buffer del framebuffer (infinito donde no hay nada), con la matriz de
+    // proyección con que se rasterizó. Solo se calcula dentro de `area`, la zona que cubre la proyección.
+    pub fn compute(&mut self, depth: &[f32], width: usize, height: usize, area: &Rect, projection: &Mat4) {
+        let area = area.intersection(&Rect { x: 0, y: 0, width, height });
+        self.width = width;
+        self.height = height;
+        self.area = area;
+        self.occlusion.clear();
+        self.occlusion.resize(width * height, 1.0);
+        self.positions.clear();
+        self.positions.resize(width * height, None);
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+
+        let (left, top) = (area.x as usize, area.y as usize);
+        let inverse_projection = projection.try_inverse().unwrap_or(Mat4::identity());
+        for y in top..top + area.height {
+            for x in left..left + area.width {
+                let z = depth[y * width + x];
+                self.positions[y * width + x] = z.is_finite().then(|| {
+                    let ndc = Vec4::new(
+                        ((x - left) as f32 + 0.5) / area.width as f32 * 2.0 - 1.0,
+                        1.0 - ((y - top) as f32 + 0.5) / area.height as f32 * 2.0,
+                        z,
+                        1.0,
+                    );
+                    let view = inverse_projection * ndc;
+                    Vec3::new(view.x, view.y, view.z) / view.w
+                });
+            }
+        }
+
+        for y in top..top + area.height {
+            for x in left..left + area.width {
+                let index = y * width + x;
+                let (Some(position), Some(normal)) = (self.positions[index], self.normal(x, y)) else {
+                    continue;
+                };
+
+                // Base tangente girada por píxel con un patrón de 4×4 que luego borra el desenfoque
+                let angle = hash((x % 4) as u32, (y % 4) as u32 + 4) * TAU;
+                let random = Vec3::new(angle.cos(), angle.sin(), 0.0);
+                let tangent = (random - normal * random.dot(&normal)).try_normalize(1e-6).unwrap_or_else(|| normal.cross(&Vec3::x()).normalize());
+                let bitangent = normal.cross(&tangent);
+
+                let mut occluded = 0.0;
+                for sample in &self.kernel {
+                    let point = position + (tangent * sample.x + bitangent * sample.y + normal * sample.z) * self.radius;
+
+                    let clip = projection * Vec4::new(point.x, point.y, point.z, 1.0);
+                    if clip.w <= 0.0 {
+                        continue;
+                    }
+                    let sx = left as f32 + ((clip.x / clip.w + 1.0) / 2.0 * area.width as f32).floor();
+                    let sy = top as f32 + ((1.0 - clip.y / clip.w) / 2.0 * area.height as f32).floor();
+                    if !area.contains(sx as isize, sy as isize) {
+                        continue;
+                    }
+
+                    // Tapa si la superficie en ese píxel está más cerca de la cámara que la muestra.
+                    // Lo que está mucho más adelante (otra pieza lejos de esta) cuenta menos.
+                    if let Some(surface) = self.positions[sy as usize * width + sx as usize] {
+                        let bias = 0.02 * self.radius;
+                        if surface.z >= point.z + bias {
+                            let range = (self.radius / (position.z - surface.z).abs().max(1e-6)).min(1.0);
+                            occluded += range * range * (3.0 - 2.0 * range);
+                        }
+                    }
+                }
+
+                self.occlusion[index] = 1.0 - (occluded / KERNEL_SIZE as f32 * self.intensity).clamp(0.0, 1.0);
+            }
+        }
+
+        self.blur(1, 0);
+        self.blur(0, 1);
+    }
+
+    // Factor para el término ambiental en el píxel (1 fuera de la zona calculada)
+    pub fn factor(&self, x: usize, y: usize) -> f32 {
+        if x < self.width && y < self.height {
+            self.occlusion[y * self.width + x]
+        } else {
+            1.0
+        }
+    }
+
+    // Normal en espacio de cámara a partir de las posiciones vecinas. En cada eje se usa el vecino más
+    // cercano en profundidad, así los bordes de la silueta no tuercen la normal.
+    fn normal(&self, x: usize, y: usize) -> Option<Vec3> {
+        let center = self.positions[y * self.width + x]?;
+        let neighbour = |dx: isize, dy: isize| {
+            let (nx, ny) = (x as isize + dx, y as isize + dy);
+            if nx < 0 || ny < 0 || nx as usize >= self.width || ny as usize >= self.height {
+                return None;
+            }
+            self.positions[ny as usize * self.width + nx as usize]
+        };
+        let closest = |forward: Option<Vec3>, backward: Option<Vec3>| match (forward, backward) {
+            (Some(f), Some(b)) if (f.z - center.z).abs() <= (b.z - center.z).abs() => Some(f - center),
+            (_, Some(b)) => Some(center - b),
+            (Some(f), None) => Some(f - center),
+            (None, None) => None,
+        };
+
+        let dx = closest(neighbour(1, 0), neighbour(-1, 0))?;
+        let dy = closest(neighbour(0, 1), neighbour(0, -1))?;
+        let normal = dx.cross(&dy).try_normalize(1e-12)?;
+
+        // Hacia la cámara, que está en el origen
+        Some(if normal.dot(&-center) < 0.0 { -normal } else { normal })
+    }
+
+    // Desenfoque bilateral en una dirección: gaussiano en pantalla, pesado por la diferencia de profundidad
+    fn blur(&mut self, step_x: isize, step_y: isize) {
+        self.scratch.clear();
+        self.scratch.extend_from_slice(&self.occlusion);
+        let depth_scale = 0.5 * self.radius;
+        let (left, top) = (self.area.x as usize, self.area.y as usize);
+
+        for y in top..top + self.area.height {
+            for x in left..left + self.area.width {
+                let index = y * self.width + x;
+                let Some(center) = self.positions[index] else {
+                    continue;
+                };
+
+                let (mut sum, mut total) = (0.0, 0.0);
+                for offset in -BLUR_RADIUS..=BLUR_RADIUS {
+                    let (nx, ny) = (x as isize + offset * step_x, y as isize + offset * step_y);
+                    if nx < 0 || ny < 0 || nx as usize >= self.width || ny as usize >= self.height {
+                        continue;
+                    }
+                    let neighbour = ny as usize * self.width + nx as usize;
+                    let Some(position) = self.positions[neighbour] else {
+                        continue;
+                    };
+
+                    let spatial = (-(offset * offset) as f32 / (2.0 * 2.5 * 2.5)).exp();
+                    let range = (-((position.z - center.z) / depth_scale).powi(2)).exp();
+                    sum += self.scratch[neighbour] * spatial * range;
+                    total += spatial * range;
+                }
+
+                if total > 0.0 {
+                    self.occlusion[index] = sum / total;
+                }
+            }
+        }
+    }
+}
+
+// Valor pseudoaleatorio en [0, 1) fijo para cada par, para que el resultado sea reproducible
+fn hash(a: u32, b: u32) -> f32 {
+    let mut h = a.wrapping_mul(0x8DA6_B343) ^ b.wrapping_mul(0xD816_3841) ^ 0x9E37_79B9;
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5BD1_E995);
+    h ^= h >> 15;
+    (h >> 8) as f32 / (1u32 << 24) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bmp::{read_bmp_file, write_bmp_file, BmpDepth, BmpFormat};
+    use crate::render::create_projection_matrix;
+
+    const SIZE: usize = 64;
+    const GOLDEN: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/ssao_crease.bmp");
+
+    // Suelo de frente a la cámara a `distance` que a partir de x = 0 se dobla hacia ella con
+    // pendiente `slope`, formando un pliegue cóncavo vertical en el centro de la imagen.
+    // Devuelve la profundidad como la deja el z-buffer (z normalizada) y la proyección usada.
+    fn crease_depth(distance: f32, slope: f32) -> (Vec<f32>, Mat4) {
+        let projection = create_projection_matrix(SIZE as f32, SIZE as f32, 45.0f32.to_radians());
+        let inverse_projection = projection.try_inverse().unwrap();
+
+        let depth = (0..SIZE * SIZE)
+            .map(|index| {
+                let (x, y) = (index % SIZE, index / SIZE);
+                let ndc_x = (x as f32 + 0.5) / SIZE as f32 * 2.0 - 1.0;
+                let ndc_y = 1.0 - (y as f32 + 0.5) / SIZE as f32 * 2.0;
+                let far = inverse_projection * Vec4::new(ndc_x, ndc_y, 1.0, 1.0);
+                let ray = Vec3::new(far.x, far.y, far.z) / far.w / -(far.z / far.w);
+
+                // Distancia `t` a lo largo del rayo (z = -t) hasta la superficie
+                let t = if ray.x <= 0.0 { distance } else { distance / (1.0 + slope * ray.x) };
+                let point = ray * t;
+
+                let clip = projection * Vec4::new(point.x, point.y, point.z, 1.0);
+                clip.z / clip.w
+            })
+            .collect();
+
+        (depth, projection)
+    }
+
+    fn crease_occlusion() -> AmbientOcclusion {
+        let (depth, projection) = crease_depth(100.0, 1.5);
+        let mut occlusion = AmbientOcclusion::new(15.0, 1.0);
+        occlusion.compute(&depth, SIZE, SIZE, &Rect { x: 0, y: 0, width: SIZE, height: SIZE }, &projection);
+        occlusion
+    }
+
+    fn to_gray(occlusion: &AmbientOcclusion) -> Vec<u32> {
+        (0..SIZE * SIZE)
+            .map(|index| {
+                let level = (occlusion.factor(index % SIZE, index / SIZE) * 255.0).round() as u32;
+                0xFF00_0000 | (level << 16) | (level << 8) | level
+            })
+            .collect()
+    }
+
+    #[test]
+    fn crease_is_darker_than_flat_floor() {
+        let occlusion = crease_occlusion();
+        let row = SIZE / 2;
+
+        let flat = occlusion.factor(4, row);
+        let crease = (SIZE / 2 - 4..SIZE / 2 + 4).map(|x| occlusion.factor(x, row)).fold(1.0, f32::min);
+
+        assert!(flat > 0.99, "flat floor should be unoccluded, got {}", flat);
+        assert!(crease < flat - 0.05, "crease {} should be darker than flat floor {}", crease, flat);
+    }
+
+    // Con `UPDATE_GOLDEN=1` se reescribe la imagen de referencia en vez de compararla
+    #[test]
+    fn matches_golden_image() {
+        let pixels = to_gray(&crease_occlusion());
+
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            let format = BmpFormat { depth: BmpDepth::Rgb24, top_down: false };
+            write_bmp_file(GOLDEN, &pixels, SIZE, SIZE, format).unwrap();
+        }
+
+        let golden = read_bmp_file(GOLDEN).unwrap();
+        assert_eq!((golden.width, golden.height), (SIZE, SIZE));
+
+        // Un nivel de margen por diferencias de redondeo en coma flotante
+        for (index, (&actual, &expected)) in pixels.iter().zip(&golden.pixels).enumerate() {
+            let (actual, expected) = ((actual & 0xFF) as i32, (expected & 0xFF) as i32);
+            assert!(
+                (actual - expected).abs() <= 1,
+                "pixel ({}, {}) is {} but the golden image has {}",
+                index % SIZE,
+                index / SIZE,
+                actual,
+                expected,
+            );
+        }
+    }
+}
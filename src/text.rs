@@ -0,0 +1,456 @@
+// text.rs
+
+use rusttype::{point, Font, GlyphId, Scale};
+use std::collections::HashMap;
+use std::fs;
+use std::rc::Rc;
+use crate::color::Color;
+
+// Alineación horizontal de cada línea respecto a la `x` dada
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextAlign {
+    Left,
+    Center,
+    Right,
+}
+
+// Contorno alrededor de cada glifo, `width` en píxeles
+#[derive(Debug, Clone, Copy)]
+pub struct Outline {
+    pub width: u32,
+    pub color: Color,
+}
+
+// Sombra desplazada que se dibuja debajo del texto
+#[derive(Debug, Clone, Copy)]
+pub struct Shadow {
+    pub offset_x: i32,
+    pub offset_y: i32,
+    pub color: Color,
+    pub opacity: f32,
+}
+
+// Apariencia del texto. Con `gradient` cada glifo va de `color` arriba a `gradient` abajo de la línea.
+#[derive(Debug, Clone, Copy)]
+pub struct TextStyle {
+    pub color: Color,
+    pub gradient: Option<Color>,
+    pub outline: Option<Outline>,
+    pub shadow: Option<Shadow>,
+}
+
+impl TextStyle {
+    pub fn new(color: Color) -> Self {
+        TextStyle {
+            color,
+            gradient: None,
+            outline: None,
+            shadow: None,
+        }
+    }
+
+    pub fn with_gradient(mut self, bottom: Color) -> Self {
+        self.gradient = Some(bottom);
+        self
+    }
+
+    pub fn with_outline(mut self, width: u32, color: Color) -> Self {
+        self.outline = Some(Outline { width, color });
+        self
+    }
+
+    pub fn with_shadow(mut self, offset_x: i32, offset_y: i32, color: Color, opacity: f32) -> Self {
+        self.shadow = Some(Shadow { offset_x, offset_y, color, opacity });
+        self
+    }
+
+    // Color de relleno a la altura `t` de la línea (0 arriba, 1 abajo)
+    pub fn color_at(&self, t: f32) -> Color {
+        match self.gradient {
+            Some(bottom) => self.color.lerp(&bottom, t.clamp(0.0, 1.0)),
+            None => self.color,
+        }
+    }
+}
+
+// Glifo de una fuente concreta a un tamaño concreto; `scale` va en pasos de `SCALE_STEPS` por píxel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GlyphKey {
+    font: usize,
+    id: GlyphId,
+    scale: u32,
+}
+
+// Pasos por píxel del tamaño con que se cachean los glifos. Así un tamaño que se recalcula
+// (por ejemplo al cambiar el tamaño de la ventana) reutiliza los glifos en vez de llenar el cache.
+const SCALE_STEPS: f32 = 4.0;
+
+// Cobertura rasterizada de un glifo, con su desplazamiento respecto al punto de origen en la línea base
+pub struct GlyphBitmap {
+    pub offset_x: i32,
+    pub offset_y: i32,
+    pub width: usize,
+    pub height: usize,
+    pub coverage: Vec<f32>,
+}
+
+// Glifo ya ubicado en pantalla (esquina superior izquierda del bitmap).
+// `line_top` y `line_height` delimitan su línea para los degradados.
+pub struct PlacedGlyph {
+    pub x: i32,
+    pub y: i32,
+    pub bitmap: Rc<GlyphBitmap>,
+    pub line_top: f32,
+    pub line_height: f32,
+    key: GlyphKey,
+}
+
+// Motor de texto: conserva las fuentes ya interpretadas y cachea los glifos rasterizados por tamaño.
+// La primera fuente es la principal; las demás se usan en orden para caracteres que no tenga.
+pub struct TextRenderer {
+    fonts: Vec<Font<'static>>,
+    cache: HashMap<GlyphKey, Rc<GlyphBitmap>>,
+    outline_cache: HashMap<(GlyphKey, u32), Rc<GlyphBitmap>>,
+    line_spacing: f32, // Multiplicador de la altura de línea
+}
+
+impl Default for TextRenderer {
+    // Usa la fuente incluida en el ejecutable
+    fn default() -> Self {
+        let font_data = include_bytes!("../fonts/mai10.ttf");
+        let font = Font::try_from_bytes(font_data as &[u8]).expect("No se pudo cargar la fuente");
+        TextRenderer::new(font)
+    }
+}
+
+impl TextRenderer {
+    pub fn new(font: Font<'static>) -> Self {
+        TextRenderer {
+            fonts: vec![font],
+            cache: HashMap::new(),
+            outline_cache: HashMap::new(),
+            line_spacing: 1.0,
+        }
+    }
+
+    // Agrega una fuente de respaldo para los caracteres que faltan en las anteriores
+    pub fn load_fallback(&mut self, path: &str) -> Result<(), String> {
+        let data = fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+        let font = Font::try_from_vec(data).ok_or_else(|| format!("{}: invalid font", path))?;
+        self.fonts.push(font);
+        Ok(())
+    }
+
+    // Primera fuente que tiene el carácter; si ninguna lo tiene se usa el glifo vacío de la principal
+    fn find_glyph(&self, c: char) -> (usize, GlyphId) {
+        self.fonts
+            .iter()
+            .enumerate()
+            .map(|(index, font)| (index, font.glyph(c).id()))
+            .find(|(_, id)| id.0 != 0)
+            .unwrap_or((0, GlyphId(0)))
+    }
+
+    // Avance horizontal del carácter y su kerning respecto al anterior, sólo si ambos son de la misma fuente
+    fn advance(&self, previous: Option<(usize, GlyphId)>, current: (usize, GlyphId), scale: Scale) -> (f32, f32) {
+        let font = &self.fonts[current.0];
+        let kerning = match previous {
+            Some((previous_font, previous_id)) if previous_font == current.0 => font.pair_kerning(scale, previous_id, current.1),
+            _ => 0.0,
+        };
+
+        (kerning, font.glyph(current.1).scaled(scale).h_metrics().advance_width)
+    }
+
+    pub fn set_line_spacing(&mut self, line_spacing: f32) {
+        self.line_spacing = line_spacing;
+    }
+
+    // Distancia entre líneas base consecutivas
+    pub fn line_height(&self, scale: f32) -> f32 {
+        let v_metrics = self.fonts[0].v_metrics(Scale::uniform(scale));
+        (v_metrics.ascent - v_metrics.descent + v_metrics.line_gap) * self.line_spacing
+    }
+
+    // Ancho de una sola línea, incluyendo el kerning
+    pub fn line_width(&self, line: &str, scale: f32) -> f32 {
+        let scale = Scale::uniform(scale);
+        let mut width = 0.0;
+        let mut previous = None;
+
+        for c in line.chars() {
+            let current = self.find_glyph(c);
+            let (kerning, advance) = self.advance(previous, current, scale);
+            width += kerning + advance;
+            previous = Some(current);
+        }
+
+        width
+    }
+
+    // Ancho y alto del bloque de texto; los saltos de línea inician una nueva línea
+    pub fn measure(&self, text: &str, scale: f32) -> (f32, f32) {
+        let width = text
+            .lines()
+            .map(|line| self.line_width(line, scale))
+            .fold(0.0, f32::max);
+        let lines = text.lines().count().max(1);
+
+        (width, lines as f32 * self.line_height(scale))
+    }
+
+    // Parte el texto en líneas que caben en `max_width`, cortando por palabras.
+    // Una palabra más ancha que la caja queda sola en su línea.
+    pub fn wrap(&self, text: &str, scale: f32, max_width: f32) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        for paragraph in text.lines() {
+            let mut line = String::new();
+
+            for word in paragraph.split_whitespace() {
+                let candidate = if line.is_empty() {
+                    word.to_string()
+                } else {
+                    format!("{} {}", line, word)
+                };
+
+                if !line.is_empty() && self.line_width(&candidate, scale) > max_width {
+                    lines.push(std::mem::replace(&mut line, word.to_string()));
+                } else {
+                    line = candidate;
+                }
+            }
+
+            lines.push(line);
+        }
+
+        lines
+    }
+
+    // Ubica los glifos del texto con `(x, y)` como esquina superior del bloque
+    pub fn layout(&mut self, text: &str, scale: f32, x: f32, y: f32, align: TextAlign) -> Vec<PlacedGlyph> {
+        let lines: Vec<&str> = text.lines().collect();
+        self.layout_lines(&lines, scale, x, y, align)
+    }
+
+    // Igual que `layout`, pero partiendo el texto en líneas de a lo sumo `max_width` píxeles
+    pub fn layout_wrapped(&mut self, text: &str, scale: f32, x: f32, y: f32, max_width: f32, align: TextAlign) -> Vec<PlacedGlyph> {
+        let lines = self.wrap(text, scale, max_width);
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        self.layout_lines(&lines, scale, x, y, align)
+    }
+
+    fn layout_lines(&mut self, lines: &[&str], scale: f32, x: f32, y: f32, align: TextAlign) -> Vec<PlacedGlyph> {
+        let v_metrics = self.fonts[0].v_metrics(Scale::uniform(scale));
+        let line_height = self.line_height(scale);
+        let mut placed = Vec::new();
+
+        for (i, line) in lines.iter().enumerate() {
+            let width = self.line_width(line, scale);
+            let start_x = match align {
+                TextAlign::Left => x,
+                TextAlign::Center => x - width / 2.0,
+                TextAlign::Right => x - width,
+            };
+            let line_top = y + i as f32 * line_height;
+            let baseline = (line_top + v_metrics.ascent).round() as i32;
+
+            let mut pen_x = start_x;
+            let mut previous = None;
+
+            for c in line.chars() {
+                let current = self.find_glyph(c);
+                let (kerning, advance) = self.advance(previous, current, Scale::uniform(scale));
+                pen_x += kerning;
+
+                // Los glifos se rasterizan en el origen, así que la pluma se ajusta a píxeles enteros
+                let key = GlyphKey { font: current.0, id: current.1, scale: (scale * SCALE_STEPS).round() as u32 };
+                let bitmap = self.glyph_bitmap(key);
+                placed.push(PlacedGlyph {
+                    x: pen_x.round() as i32 + bitmap.offset_x,
+                    y: baseline + bitmap.offset_y,
+                    bitmap,
+                    line_top,
+                    line_height: v_metrics.ascent - v_metrics.descent,
+                    key,
+                });
+
+                pen_x += advance;
+                previous = Some(current);
+            }
+        }
+
+        placed
+    }
+
+    // Versión con contorno de los glifos dados: la cobertura se dilata `width` píxeles
+    pub fn outlined(&mut self, glyphs: &[PlacedGlyph], width: u32) -> Vec<PlacedGlyph> {
+        glyphs
+            .iter()
+            .map(|glyph| {
+                let bitmap = self
+                    .outline_cache
+                    .entry((glyph.key, width))
+                    .or_insert_with(|| Rc::new(dilate(&glyph.bitmap, width)))
+                    .clone();
+
+                PlacedGlyph {
+                    x: glyph.x - width as i32,
+                    y: glyph.y - width as i32,
+                    bitmap,
+                    line_top: glyph.line_top,
+                    line_height: glyph.line_height,
+                    key: glyph.key,
+                }
+            })
+            .collect()
+    }
+
+    // Devuelve el glifo del cache o lo rasteriza la primera vez que se usa con ese tamaño
+    fn glyph_bitmap(&mut self, key: GlyphKey) -> Rc<GlyphBitmap> {
+        let font = &self.fonts[key.font];
+
+        self.cache
+            .entry(key)
+            .or_insert_with(|| {
+                let scale = Scale::uniform(key.scale as f32 / SCALE_STEPS);
+                let glyph = font.glyph(key.id).scaled(scale).positioned(point(0.0, 0.0));
+
+                let Some(bounding_box) = glyph.pixel_bounding_box() else {
+                    // Espacios y glifos sin contorno
+                    return Rc::new(GlyphBitmap { offset_x: 0, offset_y: 0, width: 0, height: 0, coverage: Vec::new() });
+                };
+
+                let width = bounding_box.width() as usize;
+                let height = bounding_box.height() as usize;
+                let mut coverage = vec![0.0; width * height];
+                glyph.draw(|gx, gy, gv| coverage[gy as usize * width + gx as usize] = gv);
+
+                Rc::new(GlyphBitmap {
+                    offset_x: bounding_box.min.x,
+                    offset_y: bounding_box.min.y,
+                    width,
+                    height,
+                    coverage,
+                })
+            })
+            .clone()
+    }
+}
+
+// Dilata la cobertura con un disco de radio `radius`, suavizando el borde exterior
+fn dilate(bitmap: &GlyphBitmap, radius: u32) -> GlyphBitmap {
+    let r = radius as i32;
+    let width = bitmap.width + 2 * radius as usize;
+    let height = bitmap.height + 2 * radius as usize;
+    let mut coverage = vec![0.0; width * height];
+
+    for sy in 0..bitmap.height {
+        for sx in 0..bitmap.width {
+            let source = bitmap.coverage[sy * bitmap.width + sx];
+            if source <= 0.0 {
+                continue;
+            }
+
+            for dy in -r - 1..=r + 1 {
+                for dx in -r - 1..=r + 1 {
+                    let falloff = (radius as f32 + 0.5 - ((dx * dx + dy * dy) as f32).sqrt()).clamp(0.0, 1.0);
+                    let x = (sx as i32 + r + dx) as usize;
+                    let y = (sy as i32 + r + dy) as usize;
+                    if falloff <= 0.0 || x >= width || y >= height {
+                        continue;
+                    }
+
+                    let index = y * width + x;
+                    coverage[index] = f32::max(coverage[index], source * falloff);
+                }
+            }
+        }
+    }
+
+    GlyphBitmap {
+        offset_x: bitmap.offset_x - r,
+        offset_y: bitmap.offset_y - r,
+        width,
+        height,
+        coverage,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_at_the_last_word_that_fits() {
+        let text = TextRenderer::default();
+        let width = text.line_width("one two", 20.0);
+
+        assert_eq!(text.wrap("one two three", 20.0, width), vec!["one two", "three"]);
+        // Justo por debajo del ancho la segunda palabra ya no cabe
+        assert_eq!(text.wrap("one two three", 20.0, width - 1.0), vec!["one", "two", "three"]);
+        // Los saltos de línea se respetan y los espacios extra se descartan
+        assert_eq!(text.wrap("one  two\nthree", 20.0, 1000.0), vec!["one two", "three"]);
+    }
+
+    #[test]
+    fn overlong_words_get_their_own_line() {
+        let text = TextRenderer::default();
+        let width = text.line_width("a b", 20.0);
+
+        assert_eq!(text.wrap("a enormous b", 20.0, width), vec!["a", "enormous", "b"]);
+        assert_eq!(text.wrap("enormous", 20.0, 1.0), vec!["enormous"]);
+    }
+
+    #[test]
+    fn measures_the_widest_line_and_every_line_height() {
+        let text = TextRenderer::default();
+        let (width, height) = text.measure("ab\nabcd", 20.0);
+
+        assert_eq!(width, text.line_width("abcd", 20.0));
+        assert_eq!(height, 2.0 * text.line_height(20.0));
+        assert!(text.line_width("abcd", 20.0) > text.line_width("ab", 20.0));
+    }
+
+    #[test]
+    fn alignment_moves_the_line_start() {
+        let mut text = TextRenderer::default();
+        let width = text.line_width("Hello", 30.0);
+        let first_x = |text: &mut TextRenderer, align| text.layout("Hello", 30.0, 200.0, 0.0, align)[0].x;
+
+        let left = first_x(&mut text, TextAlign::Left);
+        let center = first_x(&mut text, TextAlign::Center);
+        let right = first_x(&mut text, TextAlign::Right);
+
+        // Las posiciones se redondean a píxeles enteros
+        assert!(((left - center) as f32 - width / 2.0).abs() <= 1.0);
+        assert!(((left - right) as f32 - width).abs() <= 1.0);
+    }
+
+    #[test]
+    fn nearby_sizes_share_cached_glyphs() {
+        let mut text = TextRenderer::default();
+        text.layout("A", 20.0, 0.0, 0.0, TextAlign::Left);
+        text.layout("A", 20.01, 0.0, 0.0, TextAlign::Left);
+        assert_eq!(text.cache.len(), 1);
+
+        text.layout("A", 20.5, 0.0, 0.0, TextAlign::Left);
+        assert_eq!(text.cache.len(), 2);
+    }
+
+    #[test]
+    fn missing_characters_come_from_the_fallback_font() {
+        let mut text = TextRenderer::default();
+        // mai10 trae los acentos del español, pero no flechas
+        assert_eq!(text.find_glyph('→'), (0, GlyphId(0)));
+
+        text.load_fallback("fonts/DejaVuSans.ttf").unwrap();
+        let (font, id) = text.find_glyph('→');
+        assert_eq!(font, 1);
+        assert_ne!(id, GlyphId(0));
+
+        // Lo que tiene la fuente principal sigue saliendo de ella
+        assert_eq!(text.find_glyph('ñ').0, 0);
+        assert_ne!(text.find_glyph('ñ').1, GlyphId(0));
+    }
+}
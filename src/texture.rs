@@ -0,0 +1,231 @@
+// texture.rs
+
+use nalgebra_glm::Vec3;
+use image::{ImageError, ImageResult, RgbaImage};
+use image::error::{ParameterError, ParameterErrorKind};
+use std::f32::consts::PI;
+use std::path::Path;
+use crate::color::Color;
+use crate::bmp::read_bmp_file;
+
+pub struct Texture {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u32>, // ARGB, igual que el buffer del framebuffer
+}
+
+impl Texture {
+    pub fn new(width: usize, height: usize) -> Self {
+        Texture {
+            width,
+            height,
+            pixels: vec![0xFF00_0000; width * height],
+        }
+    }
+
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<u32>) -> Self {
+        assert_eq!(pixels.len(), width * height, "Pixel count does not match texture size");
+        Texture { width, height, pixels }
+    }
+
+    pub fn load(path: &str) -> ImageResult<Self> {
+        // Los BMP se leen con el decodificador propio
+        if Path::new(path).extension().is_some_and(|extension| extension.eq_ignore_ascii_case("bmp")) {
+            let image = read_bmp_file(path)?;
+            return Ok(Texture::from_pixels(image.width, image.height, image.pixels));
+        }
+
+        let img = image::open(path)?.to_rgba8();
+        let (width, height) = img.dimensions();
+
+        let pixels = img
+            .pixels()
+            .map(|p| {
+                let [r, g, b, a] = p.0;
+                ((a as u32) << 24) | ((r as u32) << 16) | ((g as u32) << 8) | b as u32
+            })
+            .collect();
+
+        Ok(Texture::from_pixels(width as usize, height as usize, pixels))
+    }
+
+    pub fn save(&self, path: &str) -> ImageResult<()> {
+        let img = RgbaImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            let pixel = self.pixels[y as usize * self.width + x as usize];
+            image::Rgba([
+                ((pixel >> 16) & 0xFF) as u8,
+                ((pixel >> 8) & 0xFF) as u8,
+                (pixel & 0xFF) as u8,
+                ((pixel >> 24) & 0xFF) as u8,
+            ])
+        });
+        img.save(path)
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, color: Color) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = 0xFF00_0000 | color.to_hex();
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Color {
+        let x = x.min(self.width - 1);
+        let y = y.min(self.height - 1);
+        Color::from_hex(self.pixels[y * self.width + x])
+    }
+
+    // Muestreo bilineal; u se repite horizontalmente y v se limita a [0, 1]
+    pub fn sample_bilinear(&self, u: f32, v: f32) -> Color {
+        self.bilinear(u, v, true)
+    }
+
+    // Muestreo bilineal limitando ambos ejes al borde de la textura
+    pub fn sample_bilinear_clamped(&self, u: f32, v: f32) -> Color {
+        self.bilinear(u, v, false)
+    }
+
+    fn bilinear(&self, u: f32, v: f32, wrap_u: bool) -> Color {
+        let u = if wrap_u { u } else { u.clamp(0.0, 1.0) };
+        let x = u * self.width as f32 - 0.5;
+        let y = v.clamp(0.0, 1.0) * self.height as f32 - 0.5;
+
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let fx = x - x0;
+        let fy = y - y0;
+
+        let w = self.width as isize;
+        let horizontal = |value: isize| {
+            if wrap_u {
+                value.rem_euclid(w) as usize
+            } else {
+                value.clamp(0, w - 1) as usize
+            }
+        };
+        let vertical = |value: isize| value.clamp(0, self.height as isize - 1) as usize;
+
+        let (x0, y0) = (x0 as isize, y0 as isize);
+        let (left, right) = (horizontal(x0), horizontal(x0 + 1));
+        let (top, bottom) = (vertical(y0), vertical(y0 + 1));
+
+        let upper = self.get(left, top).lerp(&self.get(right, top), fx);
+        let lower = self.get(left, bottom).lerp(&self.get(right, bottom), fx);
+        upper.lerp(&lower, fy)
+    }
+
+    // Muestrea la textura como un mapa equirectangular usando una dirección
+    pub fn sample_direction(&self, direction: &Vec3) -> Color {
+        let (u, v) = direction_to_equirect(direction);
+        self.sample_bilinear(u, v)
+    }
+}
+
+// Convierte una dirección en coordenadas (u, v) de una proyección equirectangular
+pub fn direction_to_equirect(direction: &Vec3) -> (f32, f32) {
+    let d = direction.normalize();
+    let u = 0.5 + d.z.atan2(d.x) / (2.0 * PI);
+    let v = 0.5 - d.y.clamp(-1.0, 1.0).asin() / PI;
+    (u, v)
+}
+
+// Inversa de `direction_to_equirect`: dirección unitaria del centro de un texel
+pub fn equirect_to_direction(u: f32, v: f32) -> Vec3 {
+    let theta = (u - 0.5) * 2.0 * PI;
+    let phi = (0.5 - v) * PI;
+    Vec3::new(phi.cos() * theta.cos(), phi.sin(), phi.cos() * theta.sin())
+}
+
+// Caras en el orden +X, -X, +Y, -Y, +Z, -Z (convención de OpenGL)
+pub struct CubeMap {
+    pub faces: Vec<Texture>,
+}
+
+impl CubeMap {
+    // Construye el cube map evaluando `f` en la dirección del centro de cada texel
+    pub fn from_fn<F: Fn(&Vec3) -> Color>(size: usize, f: F) -> Self {
+        let faces = (0..6)
+            .map(|face| {
+                let mut texture = Texture::new(size, size);
+                for y in 0..size {
+                    for x in 0..size {
+                        let u = (x as f32 + 0.5) / size as f32;
+                        let v = (y as f32 + 0.5) / size as f32;
+                        texture.set(x, y, f(&cube_face_direction(face, u, v)));
+                    }
+                }
+                texture
+            })
+            .collect();
+
+        CubeMap { faces }
+    }
+
+    // Carga seis imágenes cuadradas del mismo tamaño, en el orden +X, -X, +Y, -Y, +Z, -Z
+    pub fn load_faces(paths: &[String; 6]) -> ImageResult<Self> {
+        let faces = paths
+            .iter()
+            .map(|path| Texture::load(path))
+            .collect::<ImageResult<Vec<Texture>>>()?;
+
+        let size = faces[0].width;
+        if faces.iter().any(|face| face.width != size || face.height != size) {
+            return Err(ImageError::Parameter(ParameterError::from_kind(
+                ParameterErrorKind::DimensionMismatch,
+            )));
+        }
+
+        Ok(CubeMap { faces })
+    }
+
+    // Carga las caras `px`, `nx`, `py`, `ny`, `pz` y `nz` de un directorio
+    pub fn load_dir(dir: &str) -> ImageResult<Self> {
+        let paths = ["px", "nx", "py", "ny", "pz", "nz"].map(|name| {
+            ["png", "jpg", "jpeg", "bmp"]
+                .iter()
+                .map(|extension| format!("{}/{}.{}", dir, name, extension))
+                .find(|path| Path::new(path).exists())
+                .unwrap_or_else(|| format!("{}/{}.png", dir, name))
+        });
+
+        CubeMap::load_faces(&paths)
+    }
+
+    pub fn sample(&self, direction: &Vec3) -> Color {
+        let (face, u, v) = direction_to_cube_face(direction);
+        self.faces[face].sample_bilinear_clamped(u, v)
+    }
+}
+
+// Dirección (sin normalizar) que corresponde a (u, v) en una cara del cube map
+pub fn cube_face_direction(face: usize, u: f32, v: f32) -> Vec3 {
+    let s = 2.0 * u - 1.0;
+    let t = 2.0 * v - 1.0;
+
+    match face {
+        0 => Vec3::new(1.0, -t, -s),
+        1 => Vec3::new(-1.0, -t, s),
+        2 => Vec3::new(s, 1.0, t),
+        3 => Vec3::new(s, -1.0, -t),
+        4 => Vec3::new(s, -t, 1.0),
+        _ => Vec3::new(-s, -t, -1.0),
+    }
+}
+
+// Cara y coordenadas (u, v) en [0, 1] donde cae una dirección
+pub fn direction_to_cube_face(direction: &Vec3) -> (usize, f32, f32) {
+    let (x, y, z) = (direction.x, direction.y, direction.z);
+    let (ax, ay, az) = (x.abs(), y.abs(), z.abs());
+
+    let (face, sc, tc, ma) = if ax >= ay && ax >= az {
+        if x > 0.0 { (0, -z, -y, ax) } else { (1, z, -y, ax) }
+    } else if ay >= az {
+        if y > 0.0 { (2, x, z, ay) } else { (3, x, -z, ay) }
+    } else if z > 0.0 {
+        (4, x, -y, az)
+    } else {
+        (5, -x, -y, az)
+    };
+
+    let ma = ma.max(f32::EPSILON);
+    (face, 0.5 * (sc / ma + 1.0), 0.5 * (tc / ma + 1.0))
+}
@@ -38,6 +38,10 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
 
                 let vertex_position = v1.position * w1 + v2.position * w2 + v3.position * w3;
 
+                // Dónde estaba este punto de la superficie en el cuadro anterior
+                let previous = v1.previous_position * w1 + v2.previous_position * w2 + v3.previous_position * w3;
+                let velocity = Vec2::new(x as f32, y as f32) - previous;
+
                 fragments.push(Fragment::new(
                     x as f32, 
                     y as f32, 
@@ -46,7 +50,7 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
                     normal,
                     intensity,
                     vertex_position,
-                ));
+                ).with_velocity(velocity));
             }
         }
     }
@@ -11,6 +11,7 @@ pub struct Vertex {
     pub color: Color,
     pub transformed_position: Vec3,
     pub transformed_normal: Vec3,
+    pub previous_position: Vec2, // Posición en pantalla en el cuadro anterior, para el buffer de velocidad
 }
 
 impl Vertex {
@@ -22,6 +23,7 @@ impl Vertex {
         color: Color::new(0,0,0),
         transformed_position: position,
         transformed_normal: normal,
+        previous_position: Vec2::new(position.x, position.y),
       }
     }
   
@@ -33,6 +35,7 @@ impl Vertex {
         color,
         transformed_position: Vec3::new(0.0, 0.0, 0.0),
         transformed_normal: Vec3::new(0.0, 0.0, 0.0),
+        previous_position: Vec2::new(0.0, 0.0),
       }
     }
   
@@ -51,6 +54,7 @@ impl Vertex {
         color: Color::new(0,0,0),
         transformed_position: Vec3::new(0.0, 0.0, 0.0),
         transformed_normal: Vec3::new(0.0, 1.0, 0.0),
+        previous_position: Vec2::new(0.0, 0.0),
       }
     }
   }
\ No newline at end of file
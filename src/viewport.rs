@@ -0,0 +1,127 @@
+// viewport.rs
+
+use std::str::FromStr;
+use crate::camera::Camera;
+use crate::rect::Rect;
+use crate::render::{create_projection_matrix, create_rect_viewport_matrix, create_view_matrix, FIELD_OF_VIEW};
+use nalgebra_glm::{Mat4, Vec3};
+
+// Campo de visión más abierto para la vista del sistema, así caben todas las órbitas
+const SYSTEM_FIELD_OF_VIEW: f32 = 60.0 * std::f32::consts::PI / 180.0;
+
+// Qué cámara usa una vista
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ViewCamera {
+    Chase,  // Detrás de la nave; la mueve el jugador
+    System, // Fija, sobre la estrella mirando hacia abajo
+}
+
+// Elementos del HUD que se dibujan dentro de una vista
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HudSet {
+    pub minimap: bool,
+    pub warnings: bool,
+    pub mirror: bool,
+    pub monitor: bool,
+}
+
+impl HudSet {
+    pub const ALL: HudSet = HudSet { minimap: true, warnings: true, mirror: true, monitor: true };
+    pub const NONE: HudSet = HudSet { minimap: false, warnings: false, mirror: false, monitor: false };
+}
+
+// Rectángulo de la pantalla donde se dibuja la escena con su cámara, proyección y HUD.
+// El renderizado se recorta al rectángulo, así que las vistas pueden superponerse en el orden de la lista.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub rect: Rect,
+    pub camera: ViewCamera,
+    pub field_of_view: f32, // Vertical, en radianes
+    pub hud: HudSet,
+}
+
+impl Viewport {
+    // Matrices de vista, proyección y viewport para dibujar con `camera` dentro del rectángulo
+    pub fn matrices(&self, camera: &Camera) -> (Mat4, Mat4, Mat4) {
+        let rect = &self.rect;
+        (
+            create_view_matrix(camera.eye, camera.center, camera.up),
+            create_projection_matrix(rect.width as f32, rect.height as f32, self.field_of_view),
+            create_rect_viewport_matrix(rect.x as f32, rect.y as f32, rect.width as f32, rect.height as f32),
+        )
+    }
+}
+
+// Cámara de la vista del sistema: muy por encima de la estrella, algo inclinada para que `up` no sea paralelo
+pub fn system_camera() -> Camera {
+    Camera {
+        eye: Vec3::new(0.0, 88555.33, 8885.168),
+        center: Vec3::zeros(),
+        up: Vec3::new(0.0, 1.0, 0.0),
+        has_changed: true,
+    }
+}
+
+// Distribución de las vistas en la ventana. `M` pasa a la siguiente.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Layout {
+    Chase,            // Solo la cámara de persecución
+    System,           // Solo la vista del sistema
+    SideBySide,       // Persecución a la izquierda, sistema a la derecha
+    PictureInPicture, // Persecución con el sistema en un recuadro abajo a la derecha
+}
+
+impl Layout {
+    pub fn next(self) -> Layout {
+        match self {
+            Layout::Chase => Layout::System,
+            Layout::System => Layout::SideBySide,
+            Layout::SideBySide => Layout::PictureInPicture,
+            Layout::PictureInPicture => Layout::Chase,
+        }
+    }
+
+    // Vistas para una pantalla de `width`×`height`, en el orden en que se dibujan
+    pub fn viewports(self, width: usize, height: usize) -> Vec<Viewport> {
+        let full = Rect { x: 0, y: 0, width, height };
+        let chase = |rect: Rect, hud: HudSet| Viewport { rect, camera: ViewCamera::Chase, field_of_view: FIELD_OF_VIEW, hud };
+        let system = |rect: Rect| Viewport { rect, camera: ViewCamera::System, field_of_view: SYSTEM_FIELD_OF_VIEW, hud: HudSet::NONE };
+
+        match self {
+            Layout::Chase => vec![chase(full, HudSet::ALL)],
+            Layout::System => vec![system(full)],
+            Layout::SideBySide => {
+                let half = width / 2;
+                vec![
+                    chase(Rect { x: 0, y: 0, width: half, height }, HudSet { mirror: false, ..HudSet::ALL }),
+                    system(Rect { x: half as isize, y: 0, width: width - half, height }),
+                ]
+            }
+            Layout::PictureInPicture => {
+                let (inset_width, inset_height) = (width / 3, height / 3);
+                let margin = 20;
+                let inset = Rect {
+                    x: (width - inset_width) as isize - margin,
+                    y: (height - inset_height) as isize - margin,
+                    width: inset_width,
+                    height: inset_height,
+                };
+                vec![chase(full, HudSet::ALL), system(inset)]
+            }
+        }
+    }
+}
+
+impl FromStr for Layout {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "chase" => Ok(Layout::Chase),
+            "system" => Ok(Layout::System),
+            "side-by-side" => Ok(Layout::SideBySide),
+            "picture-in-picture" => Ok(Layout::PictureInPicture),
+            _ => Err(format!("unknown layout `{}`", value)),
+        }
+    }
+}